@@ -0,0 +1,255 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of substrate-subxt.
+//
+// subxt is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// subxt is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-subxt.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Command line tool to inspect the metadata of a substrate node and to generate
+//! `substrate-subxt` bindings from it.
+
+use std::{
+    collections::HashSet,
+    io::Write,
+    path::{
+        Path,
+        PathBuf,
+    },
+    process::{
+        Command as Process,
+        Stdio,
+    },
+};
+use structopt::StructOpt;
+use substrate_subxt::{
+    sp_core::H256,
+    ClientBuilder,
+    DefaultNodeRuntime,
+    Metadata,
+    MetadataDiff,
+    MetadataError,
+    OfflineMetadata,
+};
+use substrate_subxt_codegen::{
+    decode_metadata,
+    runtime_bindings,
+    Crates,
+};
+
+mod print;
+
+/// Inspect the metadata of a substrate node and generate bindings from it.
+#[derive(Debug, StructOpt)]
+#[structopt(name = "subxt")]
+struct Opts {
+    #[structopt(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, StructOpt)]
+enum Command {
+    /// Prints the storage entries, calls, events, constants and errors of the modules.
+    Inspect {
+        #[structopt(flatten)]
+        source: Source,
+        /// Only print the module with this name.
+        #[structopt(long)]
+        module: Option<String>,
+        /// Print the documentation.
+        #[structopt(long)]
+        docs: bool,
+        /// Print the metadata as JSON.
+        #[structopt(long)]
+        json: bool,
+    },
+    /// Saves the metadata of a node to a file.
+    ///
+    /// The file contains the SCALE encoded metadata, as read by `--file` and the
+    /// `#[runtime]` attribute macro.
+    Save {
+        /// Url of the node.
+        #[structopt(long, default_value = "ws://127.0.0.1:9944")]
+        url: String,
+        /// Also save the genesis hash, runtime version and system properties, which
+        /// can be loaded with `OfflineMetadata::load`. Files with a `.json` extension
+        /// are written as JSON.
+        #[structopt(long)]
+        offline: bool,
+        /// Path of the file.
+        #[structopt(parse(from_os_str))]
+        output: PathBuf,
+    },
+    /// Generates the `Call`, `Event`, `Store` and `Constant` bindings of all modules.
+    Codegen {
+        #[structopt(flatten)]
+        source: Source,
+        /// Write the bindings to a file instead of stdout.
+        #[structopt(long, short, parse(from_os_str))]
+        output: Option<PathBuf>,
+    },
+    /// Compares the metadata with a saved snapshot and fails if it changed.
+    Diff {
+        #[structopt(flatten)]
+        source: Source,
+        /// Path of the snapshot.
+        #[structopt(parse(from_os_str))]
+        snapshot: PathBuf,
+    },
+}
+
+/// Where the metadata is read from.
+#[derive(Debug, StructOpt)]
+struct Source {
+    /// Url of the node.
+    #[structopt(long, default_value = "ws://127.0.0.1:9944")]
+    url: String,
+    /// Read the metadata from a saved file instead of fetching it from the node.
+    #[structopt(long, parse(from_os_str))]
+    file: Option<PathBuf>,
+}
+
+#[derive(Debug, thiserror::Error)]
+enum Error {
+    #[error("{0}")]
+    Subxt(#[from] substrate_subxt::Error),
+    #[error("{0}")]
+    Codegen(#[from] substrate_subxt_codegen::Error),
+    #[error("Io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Serde json error: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[error("Metadata error: {0}")]
+    Metadata(#[from] MetadataError),
+    #[error("Metadata changed:\n{0}")]
+    Changed(MetadataDiff),
+}
+
+#[async_std::main]
+async fn main() {
+    env_logger::init();
+    if let Err(err) = run(Opts::from_args()).await {
+        eprintln!("Error: {}", err);
+        std::process::exit(1);
+    }
+}
+
+async fn run(opts: Opts) -> Result<(), Error> {
+    match opts.command {
+        Command::Inspect {
+            source,
+            module,
+            docs,
+            json,
+        } => {
+            let mut export = source.metadata().await?.export();
+            if let Some(name) = module {
+                export.modules.retain(|module| module.name == name);
+                if export.modules.is_empty() {
+                    return Err(MetadataError::ModuleNotFound(name).into())
+                }
+            }
+            if json {
+                println!("{}", serde_json::to_string_pretty(&export)?);
+            } else {
+                for module in &export.modules {
+                    print!("{}", print::module(module, docs));
+                }
+            }
+        }
+        Command::Save {
+            url,
+            offline,
+            output,
+        } => {
+            let client = ClientBuilder::<DefaultNodeRuntime>::new()
+                .set_url(url)
+                .build()
+                .await?;
+            if offline {
+                client.offline_metadata().save(&output)?;
+            } else {
+                std::fs::write(&output, client.metadata().encoded())?;
+            }
+        }
+        Command::Codegen { source, output } => {
+            let metadata = source.metadata().await?;
+            let modules = decode_metadata(metadata.encoded())?;
+            let bindings =
+                runtime_bindings(&modules, &Crates::default(), &HashSet::new());
+            let code = format_code(bindings.to_string());
+            match output {
+                Some(output) => std::fs::write(output, code)?,
+                None => print!("{}", code),
+            }
+        }
+        Command::Diff { source, snapshot } => {
+            let metadata = source.metadata().await?;
+            let diff = read_metadata(&snapshot)?.diff(&metadata);
+            if !diff.is_empty() {
+                return Err(Error::Changed(diff))
+            }
+            println!("Metadata matches {}", snapshot.display());
+        }
+    }
+    Ok(())
+}
+
+impl Source {
+    /// Reads the metadata from the file or fetches it from the node.
+    async fn metadata(&self) -> Result<Metadata, Error> {
+        if let Some(file) = &self.file {
+            return read_metadata(file)
+        }
+        let client = ClientBuilder::<DefaultNodeRuntime>::new()
+            .set_url(self.url.as_str())
+            .build()
+            .await?;
+        Ok((*client.metadata()).clone())
+    }
+}
+
+/// Reads metadata saved with `subxt save`, with or without `--offline`.
+fn read_metadata(path: &Path) -> Result<Metadata, Error> {
+    if path.extension().map(|ext| ext == "json").unwrap_or(false) {
+        return Ok(OfflineMetadata::<H256>::load(path)?.metadata)
+    }
+    let bytes = std::fs::read(path)?;
+    match Metadata::from_bytes(&bytes) {
+        Ok(metadata) => Ok(metadata),
+        Err(_) => Ok(OfflineMetadata::<H256>::from_scale(&bytes)?.metadata),
+    }
+}
+
+/// Formats the generated code with `rustfmt`, if it is installed.
+fn format_code(code: String) -> String {
+    let rustfmt = Process::new("rustfmt")
+        .args(&["--edition", "2018"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn();
+    let mut rustfmt = match rustfmt {
+        Ok(rustfmt) => rustfmt,
+        Err(_) => return code,
+    };
+    if let Some(mut stdin) = rustfmt.stdin.take() {
+        if stdin.write_all(code.as_bytes()).is_err() {
+            return code
+        }
+    }
+    match rustfmt.wait_with_output() {
+        Ok(output) if output.status.success() => {
+            String::from_utf8(output.stdout).unwrap_or(code)
+        }
+        _ => code,
+    }
+}
@@ -0,0 +1,153 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of substrate-subxt.
+//
+// subxt is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// subxt is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-subxt.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Text output of the metadata.
+
+use std::fmt::Write;
+use substrate_subxt::ModuleExport;
+
+/// Formats the items of a module, one per line.
+pub fn module(module: &ModuleExport, docs: bool) -> String {
+    let mut out = format!("{} ({})\n", module.name, module.index);
+    let storage = module.storage.iter().map(|storage| {
+        let mut line = format!("{}: ", storage.name);
+        for (hasher, key) in storage.hashers.iter().zip(&storage.keys) {
+            write!(line, "{}({}) => ", hasher, key).unwrap();
+        }
+        write!(line, "{} [{}]", storage.value, storage.modifier).unwrap();
+        (line, &storage.documentation)
+    });
+    section(&mut out, "Storage", storage, docs);
+    let calls = module.calls.iter().map(|call| {
+        let arguments = call
+            .arguments
+            .iter()
+            .map(|arg| format!("{}: {}", arg.name, arg.ty))
+            .collect::<Vec<_>>();
+        let line = format!("{} {}({})", call.index, call.name, arguments.join(", "));
+        (line, &call.documentation)
+    });
+    section(&mut out, "Calls", calls, docs);
+    let events = module.events.iter().map(|event| {
+        let arguments = event.arguments.join(", ");
+        let line = format!("{} {}({})", event.index, event.name, arguments);
+        (line, &event.documentation)
+    });
+    section(&mut out, "Events", events, docs);
+    let constants = module.constants.iter().map(|constant| {
+        let line = format!("{}: {} = {}", constant.name, constant.ty, constant.value);
+        (line, &constant.documentation)
+    });
+    section(&mut out, "Constants", constants, docs);
+    let errors = module.errors.iter().map(|error| {
+        let line = format!("{} {}", error.index, error.name);
+        (line, &error.documentation)
+    });
+    section(&mut out, "Errors", errors, docs);
+    out
+}
+
+fn section<'a>(
+    out: &mut String,
+    title: &str,
+    items: impl Iterator<Item = (String, &'a Vec<String>)>,
+    docs: bool,
+) {
+    let mut items = items.peekable();
+    if items.peek().is_none() {
+        return
+    }
+    writeln!(out, "  {}", title).unwrap();
+    for (line, documentation) in items {
+        writeln!(out, "    {}", line).unwrap();
+        if docs {
+            for doc in documentation {
+                writeln!(out, "      ///{}", doc).unwrap();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use substrate_subxt::{
+        ArgumentExport,
+        CallExport,
+        ErrorExport,
+        EventExport,
+        StorageExport,
+    };
+
+    #[test]
+    fn test_print_module() {
+        let balances = ModuleExport {
+            name: "Balances".into(),
+            index: 5,
+            storage: vec![StorageExport {
+                name: "Account".into(),
+                modifier: "Default".into(),
+                hashers: vec!["Blake2_128Concat".into()],
+                keys: vec!["T::AccountId".into()],
+                value: "AccountData<T::Balance>".into(),
+                default: "0x00".into(),
+                documentation: vec![" The balance of an account.".into()],
+            }],
+            calls: vec![CallExport {
+                name: "transfer".into(),
+                index: 0,
+                arguments: vec![
+                    ArgumentExport {
+                        name: "dest".into(),
+                        ty: "<T::Lookup as StaticLookup>::Source".into(),
+                    },
+                    ArgumentExport {
+                        name: "value".into(),
+                        ty: "Compact<T::Balance>".into(),
+                    },
+                ],
+                documentation: vec![" Transfer some liquid free balance.".into()],
+            }],
+            events: vec![EventExport {
+                name: "Transfer".into(),
+                index: 2,
+                arguments: vec!["AccountId".into(), "AccountId".into(), "Balance".into()],
+                documentation: vec![],
+            }],
+            constants: vec![],
+            errors: vec![ErrorExport {
+                name: "InsufficientBalance".into(),
+                index: 3,
+                documentation: vec![],
+            }],
+        };
+        assert_eq!(
+            module(&balances, true),
+            "Balances (5)
+  Storage
+    Account: Blake2_128Concat(T::AccountId) => AccountData<T::Balance> [Default]
+      /// The balance of an account.
+  Calls
+    0 transfer(dest: <T::Lookup as StaticLookup>::Source, value: Compact<T::Balance>)
+      /// Transfer some liquid free balance.
+  Events
+    2 Transfer(AccountId, AccountId, Balance)
+  Errors
+    3 InsufficientBalance
+"
+        );
+    }
+}
@@ -0,0 +1,65 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of substrate-subxt.
+//
+// subxt is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// subxt is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-subxt.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Metadata versions before V12, which `frame-metadata` no longer decodes.
+//!
+//! Apart from the module index added in V12 the encoding of the module metadata did not
+//! change since V10, so the modules are indexed by their position in the runtime.
+
+use codec::Decode;
+use frame_metadata::{
+    DecodeDifferent,
+    ErrorMetadata,
+    EventMetadata,
+    FunctionMetadata,
+    ModuleConstantMetadata,
+    ModuleMetadata,
+    StorageMetadata,
+};
+
+/// Module metadata of V10 and V11.
+#[derive(Decode)]
+struct LegacyModuleMetadata {
+    name: String,
+    storage: Option<StorageMetadata>,
+    calls: Option<Vec<FunctionMetadata>>,
+    event: Option<Vec<EventMetadata>>,
+    constants: Vec<ModuleConstantMetadata>,
+    errors: Vec<ErrorMetadata>,
+}
+
+/// Decodes the modules of V10 or V11 metadata, which follow the version.
+pub(crate) fn decode_modules(
+    input: &mut &[u8],
+) -> Result<Vec<ModuleMetadata>, codec::Error> {
+    let modules = Vec::<LegacyModuleMetadata>::decode(input)?;
+    let modules = modules
+        .into_iter()
+        .enumerate()
+        .map(|(index, module)| {
+            ModuleMetadata {
+                name: DecodeDifferent::Decoded(module.name),
+                storage: module.storage.map(DecodeDifferent::Decoded),
+                calls: module.calls.map(DecodeDifferent::Decoded),
+                event: module.event.map(DecodeDifferent::Decoded),
+                constants: DecodeDifferent::Decoded(module.constants),
+                errors: DecodeDifferent::Decoded(module.errors),
+                index: index as u8,
+            }
+        })
+        .collect();
+    Ok(modules)
+}
@@ -0,0 +1,884 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of substrate-subxt.
+//
+// subxt is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// subxt is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-subxt.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Generates the bindings of `substrate-subxt` for a runtime from its metadata.
+//!
+//! For every module of the runtime a Rust module is generated, containing a `#[module]`
+//! trait and the `Call`, `Event`, `Store` and `Constant` structs of the module. Used by
+//! the `#[runtime]` attribute macro and the `subxt` command line tool.
+
+#![deny(missing_docs)]
+
+use codec::Decode;
+use frame_metadata::{
+    DecodeDifferent,
+    EventMetadata,
+    FunctionMetadata,
+    ModuleConstantMetadata,
+    ModuleMetadata,
+    RuntimeMetadataV12,
+    StorageEntryMetadata,
+    StorageEntryModifier,
+    StorageEntryType,
+    META_RESERVED,
+};
+use heck::{
+    CamelCase,
+    SnakeCase,
+};
+use proc_macro2::TokenStream;
+use quote::{
+    format_ident,
+    quote,
+};
+use std::collections::{
+    BTreeSet,
+    HashSet,
+};
+
+mod legacy;
+
+/// Associated types of the `System` trait of `substrate-subxt`.
+const SYSTEM_TYPES: &[&str] = &[
+    "Index",
+    "BlockNumber",
+    "Hash",
+    "Hashing",
+    "AccountId",
+    "Address",
+    "Header",
+    "Extrinsic",
+    "AccountData",
+];
+
+/// Error decoding the metadata.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// Codec error.
+    #[error("Failed to decode metadata: {0}")]
+    Codec(#[from] codec::Error),
+    /// Invalid prefix.
+    #[error("Invalid metadata prefix")]
+    InvalidPrefix,
+    /// Unsupported metadata version.
+    #[error("Only V10, V11 and V12 metadata is supported")]
+    UnsupportedVersion,
+}
+
+/// Names of the crates the generated code refers to.
+#[derive(Clone, Debug)]
+pub struct Crates {
+    /// Name of the `substrate-subxt` crate.
+    pub subxt: syn::Ident,
+    /// Name of the `parity-scale-codec` crate.
+    pub codec: syn::Ident,
+}
+
+impl Default for Crates {
+    fn default() -> Self {
+        Self {
+            subxt: format_ident!("substrate_subxt"),
+            codec: format_ident!("codec"),
+        }
+    }
+}
+
+/// Decodes the modules of SCALE encoded metadata.
+///
+/// Modules of metadata before V12 are indexed by their position in the runtime.
+pub fn decode_metadata(bytes: &[u8]) -> Result<Vec<ModuleMetadata>, Error> {
+    let input = &mut &bytes[..];
+    if u32::decode(input)? != META_RESERVED {
+        return Err(Error::InvalidPrefix)
+    }
+    match u8::decode(input)? {
+        10 | 11 => Ok(legacy::decode_modules(input)?),
+        12 => {
+            let metadata = RuntimeMetadataV12::decode(input)?;
+            Ok(decoded(&metadata.modules).clone())
+        }
+        _ => Err(Error::UnsupportedVersion),
+    }
+}
+
+/// Generates the bindings of all modules of a runtime.
+///
+/// Types named in `defined` are used as is, all other types which are not known to
+/// `substrate-subxt` become associated types of the module traits. The defined types
+/// the modules use are registered with the events decoder and the call encoder by the
+/// generated `DefinedTypes` trait.
+pub fn runtime_bindings(
+    modules: &[ModuleMetadata],
+    crates: &Crates,
+    defined: &HashSet<String>,
+) -> TokenStream {
+    let mut used = BTreeSet::new();
+    let modules = modules
+        .iter()
+        .map(|module| {
+            let (bindings, module_used) = bindings(module, crates, defined);
+            used.extend(module_used);
+            bindings
+        })
+        .collect::<Vec<_>>();
+    let defined_types = defined_types(crates, &used);
+    quote! {
+        #defined_types
+        #(#modules)*
+    }
+}
+
+/// Generates the `DefinedTypes` trait, a supertrait of the modules using defined types
+/// which registers them like a module registers its associated types.
+fn defined_types(crates: &Crates, used: &BTreeSet<String>) -> TokenStream {
+    if used.is_empty() {
+        return quote!()
+    }
+    let subxt = &crates.subxt;
+    let register = used.iter().map(|name| {
+        let ident = format_ident!("{}", name);
+        quote!(self.register_type_decoder::<#ident>(#name);)
+    });
+    let register = quote!(#(#register)*);
+    quote! {
+        /// The types defined next to the generated modules, which they use as is.
+        pub trait DefinedTypes: #subxt::system::System {}
+
+        impl<T: #subxt::system::System> DefinedTypes for T {}
+
+        /// `EventsDecoder` extension trait.
+        pub trait DefinedTypesEventsDecoder {
+            /// Registers the defined types.
+            fn with_defined_types(&mut self);
+        }
+
+        impl<T: DefinedTypes> DefinedTypesEventsDecoder for #subxt::EventsDecoder<T> {
+            fn with_defined_types(&mut self) {
+                #register
+            }
+        }
+
+        /// `CallEncoder` extension trait.
+        pub trait DefinedTypesCallEncoder {
+            /// Registers the defined types.
+            fn with_defined_types(&mut self);
+        }
+
+        impl<T: DefinedTypes> DefinedTypesCallEncoder for #subxt::CallEncoder<T> {
+            fn with_defined_types(&mut self) {
+                #register
+            }
+        }
+    }
+}
+
+/// Returns the value of decoded metadata.
+///
+/// # Panics
+///
+/// If the value is not decoded. Metadata returned by [`decode_metadata`] is always
+/// decoded.
+fn decoded<B, O>(value: &DecodeDifferent<B, O>) -> &O {
+    match value {
+        DecodeDifferent::Decoded(value) => value,
+        DecodeDifferent::Encode(_) => panic!("Expected decoded metadata"),
+    }
+}
+
+fn text<'a>(value: &'a DecodeDifferent<&'static str, String>) -> &'a str {
+    decoded(value).as_str()
+}
+
+fn docs(
+    documentation: &DecodeDifferent<&'static [&'static str], Vec<String>>,
+) -> TokenStream {
+    let docs = decoded(documentation)
+        .iter()
+        .map(|doc| quote!(#[doc = #doc]));
+    quote!(#(#docs)*)
+}
+
+/// Returns the name as an identifier, appending an underscore to keywords.
+fn field_ident(name: &str) -> syn::Ident {
+    syn::parse_str(name).unwrap_or_else(|_| format_ident!("{}_", name))
+}
+
+/// Generates the bindings of a module.
+///
+/// A module using types named in `defined` is bound on the `DefinedTypes` trait
+/// generated by [`runtime_bindings`].
+///
+/// # Panics
+///
+/// If the metadata is not decoded.
+pub fn module_bindings(
+    module: &ModuleMetadata,
+    crates: &Crates,
+    defined: &HashSet<String>,
+) -> TokenStream {
+    bindings(module, crates, defined).0
+}
+
+/// Generates the bindings of a module, returning the defined types it uses.
+fn bindings(
+    module: &ModuleMetadata,
+    crates: &Crates,
+    defined: &HashSet<String>,
+) -> (TokenStream, BTreeSet<String>) {
+    let Crates { subxt, codec } = crates;
+    let name = text(&module.name);
+    let module_ident = format_ident!("{}", name);
+    let mod_ident = format_ident!("{}", name.to_snake_case());
+    let mut types = Types {
+        subxt,
+        codec,
+        module: &module_ident,
+        system: name == "System",
+        defined,
+        associated: BTreeSet::new(),
+        used: BTreeSet::new(),
+    };
+
+    let calls = module
+        .calls
+        .iter()
+        .flat_map(|calls| decoded(calls))
+        .filter_map(|call| types.entry(|types| types.call(call)))
+        .collect::<Vec<_>>();
+    let events = module
+        .event
+        .iter()
+        .flat_map(|events| decoded(events))
+        .filter_map(|event| types.entry(|types| types.event(event)))
+        .collect::<Vec<_>>();
+    let stores = module
+        .storage
+        .iter()
+        .flat_map(|storage| decoded(&decoded(storage).entries))
+        .filter_map(|entry| types.entry(|types| types.store(entry)))
+        .collect::<Vec<_>>();
+    let constants = decoded(&module.constants)
+        .iter()
+        .filter_map(|constant| types.entry(|types| types.constant(constant)))
+        .collect::<Vec<_>>();
+
+    let module_trait = if types.system {
+        quote! {
+            pub use #subxt::system::System;
+
+            const MODULE: &str = "System";
+        }
+    } else {
+        let associated = types.associated.iter().map(|ty| {
+            let ty = format_ident!("{}", ty);
+            quote!(type #ty: #subxt::sp_runtime::traits::Member + #codec::Codec;)
+        });
+        let defined_types = if types.used.is_empty() {
+            quote!()
+        } else {
+            quote!(+ DefinedTypes)
+        };
+        quote! {
+            /// The subset of the module's `Trait` that a client must implement.
+            #[#subxt::module]
+            pub trait #module_ident: #subxt::system::System #defined_types {
+                #(#associated)*
+            }
+        }
+    };
+    let doc = format!("Bindings for the `{}` module.", name);
+
+    let bindings = quote! {
+        #[doc = #doc]
+        #[allow(missing_docs)]
+        pub mod #mod_ident {
+            use super::*;
+            use core::marker::PhantomData;
+            use #codec::{
+                Decode,
+                Encode,
+            };
+            use #subxt::system::{
+                SystemCallEncoder,
+                SystemEventsDecoder,
+            };
+
+            #module_trait
+
+            #(#calls)*
+            #(#events)*
+            #(#stores)*
+            #(#constants)*
+        }
+    };
+    (bindings, types.used)
+}
+
+/// Converts the type names of the metadata of a module into Rust types.
+struct Types<'a> {
+    subxt: &'a syn::Ident,
+    codec: &'a syn::Ident,
+    module: &'a syn::Ident,
+    system: bool,
+    defined: &'a HashSet<String>,
+    associated: BTreeSet<String>,
+    /// The defined types which are used without arguments.
+    used: BTreeSet<String>,
+}
+
+impl<'a> Types<'a> {
+    /// Generates an item, keeping the associated and defined types it uses only if it
+    /// could be generated.
+    fn entry(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> Option<TokenStream>,
+    ) -> Option<TokenStream> {
+        let associated = self.associated.clone();
+        let used = self.used.clone();
+        let item = f(self);
+        if item.is_none() {
+            self.associated = associated;
+            self.used = used;
+        }
+        item
+    }
+
+    fn call(&mut self, call: &FunctionMetadata) -> Option<TokenStream> {
+        let name = text(&call.name);
+        if name.to_camel_case().to_snake_case() != name {
+            return None
+        }
+        let ident = format_ident!("{}Call", name.to_camel_case());
+        let module = self.module;
+        let subxt = self.subxt;
+        let mut fields = Vec::new();
+        for arg in decoded(&call.arguments) {
+            let field = field_ident(text(&arg.name));
+            let ty: syn::Type = syn::parse_str(text(&arg.ty)).ok()?;
+            let field = match compact_inner(&ty) {
+                Some(inner) => {
+                    let ty = self.convert(inner)?;
+                    quote! {
+                        #[codec(compact)]
+                        pub #field: #ty
+                    }
+                }
+                None => {
+                    let ty = self.convert(&ty)?;
+                    quote!(pub #field: #ty)
+                }
+            };
+            fields.push(field);
+        }
+        let docs = docs(&call.documentation);
+        Some(quote! {
+            #docs
+            #[derive(Clone, Debug, PartialEq, #subxt::Call, Encode)]
+            pub struct #ident<T: #module> {
+                #(#fields,)*
+                /// Runtime marker.
+                pub _runtime: PhantomData<T>,
+            }
+        })
+    }
+
+    fn event(&mut self, event: &EventMetadata) -> Option<TokenStream> {
+        let name = text(&event.name);
+        if name.to_camel_case() != name {
+            return None
+        }
+        let ident = format_ident!("{}Event", name);
+        let module = self.module;
+        let subxt = self.subxt;
+        let fields = decoded(&event.arguments)
+            .iter()
+            .map(|ty| self.parse(ty))
+            .collect::<Option<Vec<_>>>()?;
+        let docs = docs(&event.documentation);
+        Some(quote! {
+            #docs
+            #[derive(Clone, Debug, PartialEq, #subxt::Event, Decode)]
+            pub struct #ident<T: #module>(#(pub #fields,)* pub PhantomData<T>);
+        })
+    }
+
+    fn store(&mut self, entry: &StorageEntryMetadata) -> Option<TokenStream> {
+        let name = text(&entry.name);
+        if name.to_camel_case() != name {
+            return None
+        }
+        let ident = format_ident!("{}Store", name);
+        let module = self.module;
+        let subxt = self.subxt;
+        let (keys, value) = match &entry.ty {
+            StorageEntryType::Plain(value) => (vec![], value),
+            StorageEntryType::Map { key, value, .. } => {
+                let key = self.parse(text(key))?;
+                (vec![quote!(pub key: #key)], value)
+            }
+            StorageEntryType::DoubleMap {
+                key1, key2, value, ..
+            } => {
+                let key1 = self.parse(text(key1))?;
+                let key2 = self.parse(text(key2))?;
+                (
+                    vec![quote!(pub key1: #key1), quote!(pub key2: #key2)],
+                    value,
+                )
+            }
+        };
+        let value = self.parse(text(value))?;
+        let returns = match entry.modifier {
+            StorageEntryModifier::Optional => quote!(Option<#value>),
+            StorageEntryModifier::Default => value,
+        };
+        let docs = docs(&entry.documentation);
+        Some(quote! {
+            #docs
+            #[derive(Clone, Debug, PartialEq, #subxt::Store, Encode)]
+            pub struct #ident<T: #module> {
+                #(#keys,)*
+                #[store(returns = #returns)]
+                /// Runtime marker.
+                pub _runtime: PhantomData<T>,
+            }
+        })
+    }
+
+    fn constant(&mut self, constant: &ModuleConstantMetadata) -> Option<TokenStream> {
+        let name = text(&constant.name);
+        if name.to_camel_case() != name {
+            return None
+        }
+        let ident = format_ident!("{}Constant", name);
+        let module = self.module;
+        let subxt = self.subxt;
+        let ty = self.parse(text(&constant.ty))?;
+        let docs = docs(&constant.documentation);
+        Some(quote! {
+            #docs
+            #[derive(Clone, Debug, PartialEq, #subxt::Constant)]
+            pub struct #ident<T: #module> {
+                #[constant(returns = #ty)]
+                /// Runtime marker.
+                pub _runtime: PhantomData<T>,
+            }
+        })
+    }
+
+    fn parse(&mut self, ty: &str) -> Option<TokenStream> {
+        let ty: syn::Type = syn::parse_str(ty).ok()?;
+        self.convert(&ty)
+    }
+
+    fn convert(&mut self, ty: &syn::Type) -> Option<TokenStream> {
+        match ty {
+            syn::Type::Path(path) => {
+                let segments = &path.path.segments;
+                let last = segments.last()?;
+                let name = last.ident.to_string();
+                if name == "Source" || name == "LookupSource" {
+                    let subxt = self.subxt;
+                    Some(quote!(<T as #subxt::system::System>::Address))
+                } else if path.qself.is_some() || segments[0].ident == "T" {
+                    if segments.len() == 1 {
+                        return None
+                    }
+                    self.associated(&name)
+                } else {
+                    self.named(last)
+                }
+            }
+            syn::Type::Tuple(tuple) => {
+                let elems = tuple
+                    .elems
+                    .iter()
+                    .map(|ty| self.convert(ty))
+                    .collect::<Option<Vec<_>>>()?;
+                Some(quote!((#(#elems,)*)))
+            }
+            syn::Type::Array(array) => {
+                let elem = self.convert(&array.elem)?;
+                let len = &array.len;
+                Some(quote!([#elem; #len]))
+            }
+            syn::Type::Paren(paren) => self.convert(&paren.elem),
+            _ => None,
+        }
+    }
+
+    fn named(&mut self, segment: &syn::PathSegment) -> Option<TokenStream> {
+        let ident = &segment.ident;
+        let name = ident.to_string();
+        let args = match &segment.arguments {
+            syn::PathArguments::None => vec![],
+            syn::PathArguments::AngleBracketed(args) => {
+                args.args
+                    .iter()
+                    .map(|arg| {
+                        match arg {
+                            syn::GenericArgument::Type(ty) => Some(ty),
+                            _ => None,
+                        }
+                    })
+                    .collect::<Option<Vec<_>>>()?
+            }
+            syn::PathArguments::Parenthesized(_) => return None,
+        };
+        let (subxt, codec) = (self.subxt, self.codec);
+        match (name.as_str(), args.as_slice()) {
+            ("u8", [])
+            | ("u16", [])
+            | ("u32", [])
+            | ("u64", [])
+            | ("u128", [])
+            | ("i8", [])
+            | ("i16", [])
+            | ("i32", [])
+            | ("i64", [])
+            | ("i128", [])
+            | ("bool", [])
+            | ("String", []) => Some(quote!(#ident)),
+            ("Bytes", []) => Some(quote!(Vec<u8>)),
+            ("Weight", []) => Some(quote!(u64)),
+            ("Perbill", [])
+            | ("Permill", [])
+            | ("Percent", [])
+            | ("PerU16", [])
+            | ("Perquintill", [])
+            | ("DispatchError", [])
+            | ("DispatchResult", []) => Some(quote!(#subxt::sp_runtime::#ident)),
+            ("H160", []) | ("H256", []) | ("H512", []) => {
+                Some(quote!(#subxt::sp_core::#ident))
+            }
+            ("Compact", [arg]) => {
+                let arg = self.convert(arg)?;
+                Some(quote!(#codec::Compact<#arg>))
+            }
+            ("Vec", [_]) | ("Option", [_]) | ("Box", [_]) | ("Result", [_, _]) => {
+                let args = self.convert_all(&args)?;
+                Some(quote!(#ident<#(#args),*>))
+            }
+            ("BTreeSet", [_]) | ("BTreeMap", [_, _]) => {
+                let args = self.convert_all(&args)?;
+                Some(quote!(std::collections::#ident<#(#args),*>))
+            }
+            ("T", _) => None,
+            (name, _) if self.defined.contains(name) => {
+                if args.is_empty() {
+                    self.used.insert(name.to_string());
+                    Some(quote!(#ident))
+                } else {
+                    let args = self.convert_all(&args)?;
+                    Some(quote!(#ident<#(#args),*>))
+                }
+            }
+            // aliases of associated types, e.g. `BalanceOf<T>`
+            (name, [arg]) if name.ends_with("Of") && is_runtime(arg) => {
+                self.associated(name.trim_end_matches("Of"))
+            }
+            // any other type is supplied by the runtime
+            (name, _) => self.associated(name),
+        }
+    }
+
+    fn convert_all(&mut self, types: &[&syn::Type]) -> Option<Vec<TokenStream>> {
+        types.iter().map(|ty| self.convert(ty)).collect()
+    }
+
+    fn associated(&mut self, name: &str) -> Option<TokenStream> {
+        let ident = format_ident!("{}", name);
+        let subxt = self.subxt;
+        if SYSTEM_TYPES.contains(&name) {
+            Some(quote!(<T as #subxt::system::System>::#ident))
+        } else if self.system || name.is_empty() {
+            None
+        } else {
+            let module = self.module;
+            self.associated.insert(name.to_string());
+            Some(quote!(<T as #module>::#ident))
+        }
+    }
+}
+
+/// Returns the inner type of a `Compact<T>`.
+fn compact_inner(ty: &syn::Type) -> Option<&syn::Type> {
+    if let syn::Type::Path(path) = ty {
+        let segment = path.path.segments.last()?;
+        if path.qself.is_none()
+            && path.path.segments.len() == 1
+            && segment.ident == "Compact"
+        {
+            if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
+                if let Some(syn::GenericArgument::Type(ty)) = args.args.first() {
+                    return Some(ty)
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Returns `true` if the type is the runtime parameter `T`.
+fn is_runtime(ty: &syn::Type) -> bool {
+    if let syn::Type::Path(path) = ty {
+        path.qself.is_none() && path.path.is_ident("T")
+    } else {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codec::Encode;
+    use frame_metadata::{
+        EventMetadata,
+        FunctionArgumentMetadata,
+        StorageHasher,
+        StorageMetadata,
+    };
+
+    fn string(value: &str) -> DecodeDifferent<&'static str, String> {
+        DecodeDifferent::Decoded(value.to_string())
+    }
+
+    fn doc(doc: &str) -> DecodeDifferent<&'static [&'static str], Vec<String>> {
+        DecodeDifferent::Decoded(vec![doc.to_string()])
+    }
+
+    #[test]
+    fn test_balances_bindings() {
+        let module = ModuleMetadata {
+            name: string("Balances"),
+            storage: Some(DecodeDifferent::Decoded(StorageMetadata {
+                prefix: string("Balances"),
+                entries: DecodeDifferent::Decoded(vec![StorageEntryMetadata {
+                    name: string("Account"),
+                    modifier: StorageEntryModifier::Default,
+                    ty: StorageEntryType::Map {
+                        hasher: StorageHasher::Blake2_128Concat,
+                        key: string("T::AccountId"),
+                        value: string("AccountData<T::Balance>"),
+                        unused: false,
+                    },
+                    default: DecodeDifferent::Decoded(vec![]),
+                    documentation: doc(" The balance of an account."),
+                }]),
+            })),
+            calls: Some(DecodeDifferent::Decoded(vec![FunctionMetadata {
+                name: string("transfer"),
+                arguments: DecodeDifferent::Decoded(vec![
+                    FunctionArgumentMetadata {
+                        name: string("dest"),
+                        ty: string("<T::Lookup as StaticLookup>::Source"),
+                    },
+                    FunctionArgumentMetadata {
+                        name: string("value"),
+                        ty: string("Compact<T::Balance>"),
+                    },
+                ]),
+                documentation: doc(" Transfer some liquid free balance."),
+            }])),
+            event: Some(DecodeDifferent::Decoded(vec![EventMetadata {
+                name: string("Transfer"),
+                arguments: DecodeDifferent::Decoded(vec![
+                    "AccountId".to_string(),
+                    "AccountId".to_string(),
+                    "Balance".to_string(),
+                ]),
+                documentation: doc(" Transfer succeeded."),
+            }])),
+            constants: DecodeDifferent::Decoded(vec![ModuleConstantMetadata {
+                name: string("ExistentialDeposit"),
+                ty: string("BalanceOf<T>"),
+                value: DecodeDifferent::Decoded(vec![]),
+                documentation: doc(" The minimum balance."),
+            }]),
+            errors: DecodeDifferent::Decoded(vec![]),
+            index: 5,
+        };
+        let expected = quote! {
+            #[doc = "Bindings for the `Balances` module."]
+            #[allow(missing_docs)]
+            pub mod balances {
+                use super::*;
+                use core::marker::PhantomData;
+                use codec::{
+                    Decode,
+                    Encode,
+                };
+                use substrate_subxt::system::{
+                    SystemCallEncoder,
+                    SystemEventsDecoder,
+                };
+
+                /// The subset of the module's `Trait` that a client must implement.
+                #[substrate_subxt::module]
+                pub trait Balances: substrate_subxt::system::System {
+                    type Balance: substrate_subxt::sp_runtime::traits::Member + codec::Codec;
+                }
+
+                #[doc = " Transfer some liquid free balance."]
+                #[derive(Clone, Debug, PartialEq, substrate_subxt::Call, Encode)]
+                pub struct TransferCall<T: Balances> {
+                    pub dest: <T as substrate_subxt::system::System>::Address,
+                    #[codec(compact)]
+                    pub value: <T as Balances>::Balance,
+                    /// Runtime marker.
+                    pub _runtime: PhantomData<T>,
+                }
+
+                #[doc = " Transfer succeeded."]
+                #[derive(Clone, Debug, PartialEq, substrate_subxt::Event, Decode)]
+                pub struct TransferEvent<T: Balances>(
+                    pub <T as substrate_subxt::system::System>::AccountId,
+                    pub <T as substrate_subxt::system::System>::AccountId,
+                    pub <T as Balances>::Balance,
+                    pub PhantomData<T>
+                );
+
+                #[doc = " The balance of an account."]
+                #[derive(Clone, Debug, PartialEq, substrate_subxt::Store, Encode)]
+                pub struct AccountStore<T: Balances> {
+                    pub key: <T as substrate_subxt::system::System>::AccountId,
+                    #[store(returns = <T as substrate_subxt::system::System>::AccountData)]
+                    /// Runtime marker.
+                    pub _runtime: PhantomData<T>,
+                }
+
+                #[doc = " The minimum balance."]
+                #[derive(Clone, Debug, PartialEq, substrate_subxt::Constant)]
+                pub struct ExistentialDepositConstant<T: Balances> {
+                    #[constant(returns = <T as Balances>::Balance)]
+                    /// Runtime marker.
+                    pub _runtime: PhantomData<T>,
+                }
+            }
+        };
+        let result = module_bindings(&module, &Crates::default(), &HashSet::new());
+        pretty_assertions::assert_eq!(result.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn test_defined_types() {
+        let module = ModuleMetadata {
+            name: string("Herd"),
+            storage: None,
+            calls: None,
+            event: Some(DecodeDifferent::Decoded(vec![EventMetadata {
+                name: string("Born"),
+                arguments: DecodeDifferent::Decoded(vec!["Sheep".to_string()]),
+                documentation: doc(" A sheep was born."),
+            }])),
+            constants: DecodeDifferent::Decoded(vec![]),
+            errors: DecodeDifferent::Decoded(vec![]),
+            index: 1,
+        };
+        let defined = vec!["Sheep".to_string()].into_iter().collect();
+        let expected = quote! {
+            /// The types defined next to the generated modules, which they use as is.
+            pub trait DefinedTypes: substrate_subxt::system::System {}
+
+            impl<T: substrate_subxt::system::System> DefinedTypes for T {}
+
+            /// `EventsDecoder` extension trait.
+            pub trait DefinedTypesEventsDecoder {
+                /// Registers the defined types.
+                fn with_defined_types(&mut self);
+            }
+
+            impl<T: DefinedTypes> DefinedTypesEventsDecoder for substrate_subxt::EventsDecoder<T> {
+                fn with_defined_types(&mut self) {
+                    self.register_type_decoder::<Sheep>("Sheep");
+                }
+            }
+
+            /// `CallEncoder` extension trait.
+            pub trait DefinedTypesCallEncoder {
+                /// Registers the defined types.
+                fn with_defined_types(&mut self);
+            }
+
+            impl<T: DefinedTypes> DefinedTypesCallEncoder for substrate_subxt::CallEncoder<T> {
+                fn with_defined_types(&mut self) {
+                    self.register_type_decoder::<Sheep>("Sheep");
+                }
+            }
+
+            #[doc = "Bindings for the `Herd` module."]
+            #[allow(missing_docs)]
+            pub mod herd {
+                use super::*;
+                use core::marker::PhantomData;
+                use codec::{
+                    Decode,
+                    Encode,
+                };
+                use substrate_subxt::system::{
+                    SystemCallEncoder,
+                    SystemEventsDecoder,
+                };
+
+                /// The subset of the module's `Trait` that a client must implement.
+                #[substrate_subxt::module]
+                pub trait Herd: substrate_subxt::system::System + DefinedTypes {}
+
+                #[doc = " A sheep was born."]
+                #[derive(Clone, Debug, PartialEq, substrate_subxt::Event, Decode)]
+                pub struct BornEvent<T: Herd>(pub Sheep, pub PhantomData<T>);
+            }
+        };
+        let result = runtime_bindings(&[module], &Crates::default(), &defined);
+        pretty_assertions::assert_eq!(result.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn test_decode_legacy_metadata() {
+        // (name, storage, calls, event, constants, errors) of V11 modules
+        let module = |name: &str, call: Option<&str>| {
+            (
+                name.to_string(),
+                None::<()>,
+                call.map(|call| {
+                    vec![(call.to_string(), Vec::<()>::new(), Vec::<String>::new())]
+                }),
+                None::<()>,
+                Vec::<()>::new(),
+                Vec::<()>::new(),
+            )
+        };
+        let mut bytes = META_RESERVED.encode();
+        bytes.push(11);
+        bytes.extend(
+            vec![module("System", None), module("Balances", Some("transfer"))].encode(),
+        );
+        // extrinsic version and signed extensions
+        bytes.extend((4u8, Vec::<String>::new()).encode());
+
+        let modules = decode_metadata(&bytes).unwrap();
+        assert_eq!(modules.len(), 2);
+        assert_eq!(text(&modules[1].name), "Balances");
+        assert_eq!(modules[1].index, 1);
+        assert_eq!(decoded(modules[1].calls.as_ref().unwrap()).len(), 1);
+
+        bytes[4] = 9;
+        assert!(matches!(
+            decode_metadata(&bytes),
+            Err(Error::UnsupportedVersion)
+        ));
+    }
+}
@@ -24,7 +24,6 @@ use substrate_subxt::{
     sp_core::Decode,
     ClientBuilder,
     DefaultNodeRuntime,
-    EventSubscription,
     EventsDecoder,
     PairSigner,
 };
@@ -37,14 +36,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let dest = AccountKeyring::Bob.to_account_id().into();
 
     let client = ClientBuilder::<DefaultNodeRuntime>::new().build().await?;
-    let sub = client.subscribe_events().await?;
-    let mut decoder = EventsDecoder::<DefaultNodeRuntime>::new(client.metadata().clone());
+    let mut decoder =
+        EventsDecoder::<DefaultNodeRuntime>::new((*client.metadata()).clone());
     decoder.with_balances();
-    let mut sub = EventSubscription::<DefaultNodeRuntime>::new(sub, decoder);
+    let mut sub = client.event_subscription(decoder).await?;
     sub.filter_event::<TransferEvent<_>>();
     client.transfer(&signer, &dest, 10_000).await?;
-    let raw = sub.next().await.unwrap().unwrap();
-    let event = TransferEvent::<DefaultNodeRuntime>::decode(&mut &raw.data[..]);
+    let record = sub.next().await.unwrap().unwrap();
+    let event = TransferEvent::<DefaultNodeRuntime>::decode(&mut &record.event.data[..]);
     if let Ok(e) = event {
         println!("Balance transfer success: value: {:?}", e.amount);
     } else {
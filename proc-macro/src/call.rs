@@ -43,6 +43,7 @@ pub fn call(s: Structure) -> TokenStream {
     let filtered_fields = utils::filter_fields(&fields, &marker);
     let args = utils::fields_to_args(&filtered_fields);
     let build_struct = utils::build_struct(ident, &fields);
+    let arg_types = utils::arg_types(&bindings);
     let call_trait = format_ident!("{}CallExt", call_name.to_camel_case());
     let call = format_ident!("{}", call_name);
     let call_and_watch = format_ident!("{}_and_watch", call_name);
@@ -51,6 +52,7 @@ pub fn call(s: Structure) -> TokenStream {
         impl#generics #subxt::Call<T> for #ident<#(#params),*> {
             const MODULE: &'static str = MODULE;
             const FUNCTION: &'static str = #call_name;
+            const ARG_TYPES: Option<&'static [&'static str]> = Some(&[#(#arg_types),*]);
             fn events_decoder(
                 decoder: &mut #subxt::EventsDecoder<T>,
             ) {
@@ -58,6 +60,14 @@ pub fn call(s: Structure) -> TokenStream {
             }
         }
 
+        impl#generics #subxt::CheckCompatibility<T> for #ident<#(#params),*> {
+            fn check_compatibility(
+                metadata: &#subxt::Metadata,
+            ) -> Vec<#subxt::Incompatibility> {
+                #subxt::check_call::<T, Self>(metadata)
+            }
+        }
+
         /// Call extension trait.
         pub trait #call_trait<T: #subxt::Runtime + #module> {
             /// Create and submit an extrinsic.
@@ -75,10 +85,7 @@ pub fn call(s: Structure) -> TokenStream {
             ) -> core::pin::Pin<Box<dyn core::future::Future<Output = Result<#subxt::ExtrinsicSuccess<T>, #subxt::Error>> + Send + 'a>>;
         }
 
-        impl<T: #subxt::Runtime + #module> #call_trait<T> for #subxt::Client<T>
-        where
-            <<T::Extra as #subxt::SignedExtra<T>>::Extra as #subxt::SignedExtension>::AdditionalSigned: Send + Sync,
-        {
+        impl<T: #subxt::Runtime + #module> #call_trait<T> for #subxt::Client<T> {
             fn #call<'a>(
                 &'a self,
                 signer: &'a (dyn #subxt::Signer<T> + Send + Sync),
@@ -118,6 +125,10 @@ mod tests {
             impl<'a, T: Balances> substrate_subxt::Call<T> for TransferCall<'a, T> {
                 const MODULE: &'static str = MODULE;
                 const FUNCTION: &'static str = "transfer";
+                const ARG_TYPES: Option<&'static [&'static str]> = Some(&[
+                    "< T as System > :: Address",
+                    "Compact<T :: Balance>"
+                ]);
                 fn events_decoder(
                     decoder: &mut substrate_subxt::EventsDecoder<T>,
                 ) {
@@ -125,6 +136,14 @@ mod tests {
                 }
             }
 
+            impl<'a, T: Balances> substrate_subxt::CheckCompatibility<T> for TransferCall<'a, T> {
+                fn check_compatibility(
+                    metadata: &substrate_subxt::Metadata,
+                ) -> Vec<substrate_subxt::Incompatibility> {
+                    substrate_subxt::check_call::<T, Self>(metadata)
+                }
+            }
+
             /// Call extension trait.
             pub trait TransferCallExt<T: substrate_subxt::Runtime + Balances> {
                 /// Create and submit an extrinsic.
@@ -144,10 +163,7 @@ mod tests {
                 ) -> core::pin::Pin<Box<dyn core::future::Future<Output = Result<substrate_subxt::ExtrinsicSuccess<T>, substrate_subxt::Error>> + Send + 'a>>;
             }
 
-            impl<T: substrate_subxt::Runtime + Balances> TransferCallExt<T> for substrate_subxt::Client<T>
-            where
-                <<T::Extra as substrate_subxt::SignedExtra<T>>::Extra as substrate_subxt::SignedExtension>::AdditionalSigned: Send + Sync,
-            {
+            impl<T: substrate_subxt::Runtime + Balances> TransferCallExt<T> for substrate_subxt::Client<T> {
                 fn transfer<'a>(
                     &'a self,
                     signer: &'a (dyn substrate_subxt::Signer<T> + Send + Sync),
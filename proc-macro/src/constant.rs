@@ -0,0 +1,139 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of substrate-subxt.
+//
+// subxt is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// subxt is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-subxt.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate::utils;
+use heck::{
+    CamelCase,
+    SnakeCase,
+};
+use proc_macro2::TokenStream;
+use proc_macro_error::abort;
+use quote::{
+    format_ident,
+    quote,
+};
+use syn::parse::{
+    Parse,
+    ParseStream,
+};
+use synstructure::Structure;
+
+mod kw {
+    use syn::custom_keyword;
+
+    custom_keyword!(returns);
+}
+
+#[derive(Debug)]
+enum ConstantAttr {
+    Returns(utils::Attr<kw::returns, syn::Type>),
+}
+
+impl Parse for ConstantAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        Ok(Self::Returns(input.parse()?))
+    }
+}
+
+type ConstantAttrs = utils::Attrs<ConstantAttr>;
+
+fn parse_returns_attr(attr: &syn::Attribute) -> Option<syn::Type> {
+    let attrs: ConstantAttrs = syn::parse2(attr.tokens.clone())
+        .map_err(|err| abort!("{}", err))
+        .unwrap();
+    attrs.attrs.into_iter().next().map(|attr| {
+        let ConstantAttr::Returns(attr) = attr;
+        attr.value
+    })
+}
+
+pub fn constant(s: Structure) -> TokenStream {
+    let subxt = utils::use_crate("substrate-subxt");
+    let ident = &s.ast().ident;
+    let generics = &s.ast().generics;
+    let params = utils::type_params(generics);
+    let module = utils::module_name(generics);
+    let constant_name = utils::ident_to_name(ident, "Constant").to_camel_case();
+    let constant = format_ident!("{}", constant_name.to_snake_case());
+    let constant_trait = format_ident!("{}ConstantExt", constant_name);
+    let bindings = utils::bindings(&s);
+    let ret = bindings
+        .iter()
+        .filter_map(|bi| bi.ast().attrs.iter().filter_map(parse_returns_attr).next())
+        .next()
+        .unwrap_or_else(|| {
+            abort!(ident, "#[constant(returns = ..)] needs to be specified.")
+        });
+
+    quote! {
+        impl#generics #subxt::Constant<T> for #ident<#(#params),*> {
+            const MODULE: &'static str = MODULE;
+            const CONSTANT: &'static str = #constant_name;
+            type Returns = #ret;
+        }
+
+        /// Constant extension trait.
+        pub trait #constant_trait<T: #subxt::Runtime + #module> {
+            /// Retrieve the constant.
+            fn #constant(&self) -> Result<#ret, #subxt::Error>;
+        }
+
+        impl<T: #subxt::Runtime + #module> #constant_trait<T> for #subxt::Client<T> {
+            fn #constant(&self) -> Result<#ret, #subxt::Error> {
+                self.constant::<#ident<#(#params),*>>()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_existential_deposit_constant() {
+        let input = quote! {
+            #[derive(Constant)]
+            pub struct ExistentialDepositConstant<T: Balances> {
+                #[constant(returns = T::Balance)]
+                pub _runtime: PhantomData<T>,
+            }
+        };
+        let expected = quote! {
+            impl<T: Balances> substrate_subxt::Constant<T> for ExistentialDepositConstant<T> {
+                const MODULE: &'static str = MODULE;
+                const CONSTANT: &'static str = "ExistentialDeposit";
+                type Returns = T::Balance;
+            }
+
+            /// Constant extension trait.
+            pub trait ExistentialDepositConstantExt<T: substrate_subxt::Runtime + Balances> {
+                /// Retrieve the constant.
+                fn existential_deposit(&self) -> Result<T::Balance, substrate_subxt::Error>;
+            }
+
+            impl<T: substrate_subxt::Runtime + Balances> ExistentialDepositConstantExt<T> for substrate_subxt::Client<T> {
+                fn existential_deposit(&self) -> Result<T::Balance, substrate_subxt::Error> {
+                    self.constant::<ExistentialDepositConstant<T>>()
+                }
+            }
+        };
+        let derive_input = syn::parse2(input).unwrap();
+        let s = Structure::new(&derive_input);
+        let result = constant(s);
+        utils::assert_proc_macro(result, expected);
+    }
+}
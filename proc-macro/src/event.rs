@@ -35,11 +35,22 @@ pub fn event(s: Structure) -> TokenStream {
     let event_name = utils::ident_to_name(ident, "Event").to_camel_case();
     let event = format_ident!("{}", event_name.to_snake_case());
     let event_trait = format_ident!("{}EventExt", event_name);
+    let bindings = utils::bindings(&s);
+    let arg_types = utils::arg_types(&bindings);
 
     quote! {
         impl<T: #module> #subxt::Event<T> for #ident<T> {
             const MODULE: &'static str = MODULE;
             const EVENT: &'static str = #event_name;
+            const ARG_TYPES: Option<&'static [&'static str]> = Some(&[#(#arg_types),*]);
+        }
+
+        impl<T: #module> #subxt::CheckCompatibility<T> for #ident<T> {
+            fn check_compatibility(
+                metadata: &#subxt::Metadata,
+            ) -> Vec<#subxt::Incompatibility> {
+                #subxt::check_event::<T, Self>(metadata)
+            }
         }
 
         /// Event extension trait.
@@ -74,6 +85,19 @@ mod tests {
             impl<T: Balances> substrate_subxt::Event<T> for TransferEvent<T> {
                 const MODULE: &'static str = MODULE;
                 const EVENT: &'static str = "Transfer";
+                const ARG_TYPES: Option<&'static [&'static str]> = Some(&[
+                    "< T as System > :: AccountId",
+                    "< T as System > :: AccountId",
+                    "T :: Balance"
+                ]);
+            }
+
+            impl<T: Balances> substrate_subxt::CheckCompatibility<T> for TransferEvent<T> {
+                fn check_compatibility(
+                    metadata: &substrate_subxt::Metadata,
+                ) -> Vec<substrate_subxt::Incompatibility> {
+                    substrate_subxt::check_event::<T, Self>(metadata)
+                }
             }
 
             /// Event extension trait.
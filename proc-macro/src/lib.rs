@@ -17,8 +17,10 @@
 extern crate proc_macro;
 
 mod call;
+mod constant;
 mod event;
 mod module;
+mod runtime;
 mod store;
 mod test;
 mod utils;
@@ -30,10 +32,11 @@ use synstructure::{
     Structure,
 };
 
-/// Register type sizes for [EventsDecoder](struct.EventsDecoder.html) and set the `MODULE`.
+/// Register type decoders for [EventsDecoder](struct.EventsDecoder.html) and [CallEncoder](struct.CallEncoder.html) and set the `MODULE`.
 ///
-/// The `module` macro registers the type sizes of the associated types of a trait so that [EventsDecoder](struct.EventsDecoder.html)
-/// can decode events of that type when received from Substrate. It also sets the `MODULE` constant
+/// The `module` macro registers the type decoders of the associated types of a trait so that [EventsDecoder](struct.EventsDecoder.html)
+/// can decode events of that type when received from Substrate, and [CallEncoder](struct.CallEncoder.html) can encode
+/// calls with arguments of that type. It also sets the `MODULE` constant
 /// to the name of the trait (must match the name of the Substrate pallet) that enables the [Call](), [Event]() and [Store]() macros to work.
 ///
 /// If you do not want an associated type to be registered, likely because you never expect it as part of a response payload to be decoded, use `#[module(ignore)]` on the type.
@@ -73,21 +76,76 @@ use synstructure::{
 /// {
 ///     fn with_herd(&mut self) {
 ///         self.with_husbandry();
-///         self.register_type_size::<T::Hooves>("Hooves");
-///         self.register_type_size::<T::Wool>("Wool");
+///         self.register_type_decoder::<T::Hooves>("Hooves");
+///         self.register_type_decoder::<T::Wool>("Wool");
+///     }
+/// }
+///
+/// // `CallEncoder` extension trait.
+/// pub trait HerdCallEncoder {
+///     // Registers this modules types.
+///     fn with_herd(&mut self);
+/// }
+///
+/// impl<T: Herd> HerdCallEncoder for
+///     substrate_subxt::CallEncoder<T>
+/// {
+///     fn with_herd(&mut self) {
+///         self.with_husbandry();
+///         self.register_type_decoder::<T::Hooves>("Hooves");
+///         self.register_type_decoder::<T::Wool>("Wool");
 ///     }
 /// }
 /// ```
 ///
 /// The following type sizes are registered by default: `bool, u8, u32, AccountId, AccountIndex,
 /// AuthorityId, AuthorityIndex, AuthorityWeight, BlockNumber, DispatchInfo, Hash, Kind,
-/// MemberCount, PhantomData, PropIndex, ProposalIndex, ReferendumIndex, SessionIndex, VoteThreshold`
+/// MemberCount, PhantomData, PropIndex, ProposalIndex, ReferendumIndex, SessionIndex, VoteThreshold`.
+/// `Compact<..>` encoded types are always decoded.
 #[proc_macro_attribute]
 #[proc_macro_error]
 pub fn module(args: TokenStream, input: TokenStream) -> TokenStream {
     module::module(args.into(), input.into()).into()
 }
 
+/// Generates the bindings of a runtime from its metadata.
+///
+/// The attribute is applied to an inline module and takes the path of a file with the
+/// SCALE encoded metadata, relative to the crate root. For every module of the runtime a
+/// submodule is added, containing a [module](attr.module.html) trait and the
+/// [Call](derive.Call.html), [Event](derive.Event.html), [Store](derive.Store.html) and
+/// [Constant](derive.Constant.html) structs of the module.
+///
+/// Example:
+///
+/// ```ignore
+/// #[subxt::runtime(metadata = "node.scale")]
+/// pub mod node {
+///     pub use frame_support::weights::DispatchInfo;
+/// }
+///
+/// impl node::balances::Balances for MyRuntime {
+///     type Balance = u128;
+/// }
+/// ```
+///
+/// Types of the metadata are mapped to the associated types of `System`, to types defined
+/// or imported in the annotated module and to a few well known types. Any other type
+/// becomes an associated type of the generated module trait, to be specified by the
+/// runtime. Items using types that cannot be expressed are skipped. The defined types
+/// which the modules use are registered with the events decoder and the call encoder
+/// through the generated `DefinedTypes` trait.
+///
+/// The crate needs to depend on `parity-scale-codec`, like for hand-written modules.
+///
+/// The metadata file is written by `subxt save`, and `subxt codegen` prints the
+/// generated code.
+#[proc_macro_attribute]
+#[proc_macro_error]
+pub fn runtime(args: TokenStream, input: TokenStream) -> TokenStream {
+    runtime::runtime(args.into(), input.into()).into()
+}
+
 decl_derive!(
     [Call] =>
     /// Derive macro that implements [substrate_subxt::Call](../substrate_subxt/trait.Call.html) for your struct
@@ -153,6 +211,30 @@ fn store(s: Structure) -> TokenStream {
     store::store(s).into()
 }
 
+decl_derive!(
+    [Constant, attributes(constant)] =>
+    /// Derive macro that implements [substrate_subxt::Constant](../substrate_subxt/trait.Constant.html)
+    /// for your struct and adds an extension trait to read the constant from the metadata.
+    ///
+    /// Example:
+    /// ```rust,ignore
+    /// #[derive(Constant)]
+    /// pub struct ExistentialDepositConstant<T: Balances> {
+    ///     #[constant(returns = T::Balance)]
+    ///     /// Runtime marker.
+    ///     pub _runtime: PhantomData<T>,
+    /// }
+    /// ```
+    ///
+    /// The derived `ExistentialDepositConstantExt` trait adds an `existential_deposit()` method
+    /// to [Client](../substrate_subxt/struct.Client.html), which decodes the value of the
+    /// `ExistentialDeposit` constant of the module into the `returns` type.
+    #[proc_macro_error] constant
+);
+fn constant(s: Structure) -> TokenStream {
+    constant::constant(s).into()
+}
+
 #[proc_macro]
 #[proc_macro_error]
 pub fn subxt_test(input: TokenStream) -> TokenStream {
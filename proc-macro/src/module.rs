@@ -66,10 +66,15 @@ fn events_decoder_trait_name(module: &syn::Ident) -> syn::Ident {
     format_ident!("{}EventsDecoder", module.to_string())
 }
 
+fn call_encoder_trait_name(module: &syn::Ident) -> syn::Ident {
+    format_ident!("{}CallEncoder", module.to_string())
+}
+
 fn with_module_ident(module: &syn::Ident) -> syn::Ident {
     format_ident!("with_{}", module.to_string().to_snake_case())
 }
-/// Attribute macro that registers the type sizes used by the module; also sets the `MODULE` constant.
+/// Attribute macro that registers the types used by the module with the events decoder and
+/// the call encoder; also sets the `MODULE` constant.
 pub fn module(_args: TokenStream, tokens: TokenStream) -> TokenStream {
     let input: Result<syn::ItemTrait, _> = syn::parse2(tokens.clone());
     let input = if let Ok(input) = input {
@@ -83,33 +88,42 @@ pub fn module(_args: TokenStream, tokens: TokenStream) -> TokenStream {
     let module = &input.ident;
     let module_name = module.to_string();
     let module_events_decoder = events_decoder_trait_name(module);
+    let module_call_encoder = call_encoder_trait_name(module);
     let with_module = with_module_ident(module);
 
-    let bounds = input.supertraits.iter().filter_map(|bound| {
-        if let syn::TypeParamBound::Trait(syn::TraitBound { path, .. }) = bound {
-            let module = utils::path_to_ident(path);
-            let with_module = with_module_ident(module);
-            Some(quote! {
-                self.#with_module();
-            })
-        } else {
-            None
-        }
-    });
-    let types = input.items.iter().filter_map(|item| {
-        if let syn::TraitItem::Type(ty) = item {
-            if ignore(&ty.attrs) {
-                return None
-            }
-            let ident = &ty.ident;
-            let ident_str = ident.to_string();
-            Some(quote! {
-                self.register_type_size::<T::#ident>(#ident_str);
-            })
-        } else {
-            None
-        }
-    });
+    let bounds = input
+        .supertraits
+        .iter()
+        .filter_map(|bound| {
+            if let syn::TypeParamBound::Trait(syn::TraitBound { path, .. }) = bound {
+                let module = utils::path_to_ident(path);
+                let with_module = with_module_ident(module);
+                Some(quote! {
+                    self.#with_module();
+                })
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+    let types = input
+        .items
+        .iter()
+        .filter_map(|item| {
+            if let syn::TraitItem::Type(ty) = item {
+                if ignore(&ty.attrs) {
+                    return None
+                }
+                let ident = &ty.ident;
+                let ident_str = ident.to_string();
+                Some(quote! {
+                    self.register_type_decoder::<T::#ident>(#ident_str);
+                })
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<_>>();
 
     quote! {
         #input
@@ -130,6 +144,21 @@ pub fn module(_args: TokenStream, tokens: TokenStream) -> TokenStream {
                 #(#types)*
             }
         }
+
+        /// `CallEncoder` extension trait.
+        pub trait #module_call_encoder {
+            /// Registers this modules types.
+            fn #with_module(&mut self);
+        }
+
+        impl<T: #module> #module_call_encoder for
+            #subxt::CallEncoder<T>
+        {
+            fn #with_module(&mut self) {
+                #(#bounds)*
+                #(#types)*
+            }
+        }
     }
 }
 
@@ -179,7 +208,22 @@ mod tests {
             {
                 fn with_balances(&mut self) {
                     self.with_system();
-                    self.register_type_size::<T::Balance>("Balance");
+                    self.register_type_decoder::<T::Balance>("Balance");
+                }
+            }
+
+            /// `CallEncoder` extension trait.
+            pub trait BalancesCallEncoder {
+                /// Registers this modules types.
+                fn with_balances(&mut self);
+            }
+
+            impl<T: Balances> BalancesCallEncoder for
+                substrate_subxt::CallEncoder<T>
+            {
+                fn with_balances(&mut self) {
+                    self.with_system();
+                    self.register_type_decoder::<T::Balance>("Balance");
                 }
             }
         };
@@ -220,8 +264,24 @@ mod tests {
             {
                 fn with_herd(&mut self) {
                     self.with_husbandry();
-                    self.register_type_size::<T::Hoves>("Hoves");
-                    self.register_type_size::<T::Wool>("Wool");
+                    self.register_type_decoder::<T::Hoves>("Hoves");
+                    self.register_type_decoder::<T::Wool>("Wool");
+                }
+            }
+
+            /// `CallEncoder` extension trait.
+            pub trait HerdCallEncoder {
+                /// Registers this modules types.
+                fn with_herd(&mut self);
+            }
+
+            impl<T: Herd> HerdCallEncoder for
+                substrate_subxt::CallEncoder<T>
+            {
+                fn with_herd(&mut self) {
+                    self.with_husbandry();
+                    self.register_type_decoder::<T::Hoves>("Hoves");
+                    self.register_type_decoder::<T::Wool>("Wool");
                 }
             }
         };
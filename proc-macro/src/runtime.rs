@@ -0,0 +1,112 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of substrate-subxt.
+//
+// subxt is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// subxt is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-subxt.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate::utils;
+use proc_macro2::TokenStream;
+use proc_macro_error::abort;
+use quote::quote;
+use std::{
+    collections::HashSet,
+    path::Path,
+};
+use substrate_subxt_codegen::{
+    decode_metadata,
+    runtime_bindings,
+    Crates,
+};
+
+mod kw {
+    use syn::custom_keyword;
+
+    custom_keyword!(metadata);
+}
+
+type RuntimeArgs = utils::Attr<kw::metadata, syn::LitStr>;
+
+pub fn runtime(args: TokenStream, input: TokenStream) -> TokenStream {
+    let args: RuntimeArgs = syn::parse2(args).map_err(|err| abort!("{}", err)).unwrap();
+    let mut module: syn::ItemMod =
+        syn::parse2(input).map_err(|err| abort!("{}", err)).unwrap();
+    let path = Path::new(&std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default())
+        .join(args.value.value());
+    let bytes = std::fs::read(&path).unwrap_or_else(|err| {
+        abort!(args.value, "Failed to read {}: {}", path.display(), err)
+    });
+    let modules = decode_metadata(&bytes)
+        .unwrap_or_else(|err| abort!(args.value, "{}: {}", path.display(), err));
+
+    let (brace, mut items) = match module.content.take() {
+        Some(content) => content,
+        None => {
+            abort!(
+                module.ident,
+                "#[runtime] needs to be applied to an inline module"
+            )
+        }
+    };
+    let crates = Crates {
+        subxt: utils::use_crate("substrate-subxt"),
+        codec: utils::use_crate("parity-scale-codec"),
+    };
+    let generated = runtime_bindings(&modules, &crates, &defined_types(&items));
+    // rebuilds the bindings when the metadata file changes
+    let path = path.display().to_string();
+    items.push(syn::Item::Verbatim(quote! {
+        const _: &[u8] = include_bytes!(#path);
+        #generated
+    }));
+    module.content = Some((brace, items));
+    quote!(#module)
+}
+
+/// Returns the names of the types defined or imported in the module, which are used
+/// as is instead of becoming associated types.
+fn defined_types(items: &[syn::Item]) -> HashSet<String> {
+    fn use_tree(tree: &syn::UseTree, names: &mut HashSet<String>) {
+        match tree {
+            syn::UseTree::Path(path) => use_tree(&path.tree, names),
+            syn::UseTree::Name(name) => {
+                names.insert(name.ident.to_string());
+            }
+            syn::UseTree::Rename(rename) => {
+                names.insert(rename.rename.to_string());
+            }
+            syn::UseTree::Group(group) => {
+                for tree in &group.items {
+                    use_tree(tree, names);
+                }
+            }
+            syn::UseTree::Glob(_) => {}
+        }
+    }
+    let mut names = HashSet::new();
+    for item in items {
+        match item {
+            syn::Item::Type(ty) => {
+                names.insert(ty.ident.to_string());
+            }
+            syn::Item::Struct(s) => {
+                names.insert(s.ident.to_string());
+            }
+            syn::Item::Enum(e) => {
+                names.insert(e.ident.to_string());
+            }
+            syn::Item::Use(u) => use_tree(&u.tree, &mut names),
+            _ => {}
+        }
+    }
+    names
+}
@@ -81,6 +81,7 @@ pub fn store(s: Structure) -> TokenStream {
     let filtered_fields = utils::filter_fields(&fields, &marker);
     let args = utils::fields_to_args(&filtered_fields);
     let build_struct = utils::build_struct(ident, &fields);
+    let key_types = utils::arg_types(&bindings);
     let (ret, store_ret, uses_default) = bindings
         .iter()
         .filter_map(|bi| bi.ast().attrs.iter().filter_map(parse_returns_attr).next())
@@ -111,6 +112,16 @@ pub fn store(s: Structure) -> TokenStream {
     let keys = filtered_fields
         .iter()
         .map(|(field, _)| quote!(&self.#field));
+    let key_tys = filtered_fields
+        .iter()
+        .map(|(_, ty)| utils::owned_type(ty))
+        .collect::<Vec<_>>();
+    let (key_ty, store_ty_params) = match key_tys.as_slice() {
+        [] => (quote!(()), quote!()),
+        [key] => (quote!(#key), quote!(::<#key>)),
+        keys => (quote!((#(#keys),*)), quote!(::<#(#keys),*>)),
+    };
+    let return_type = utils::type_name(&store_ret);
     let key_iter = quote!(#subxt::KeyIter<T, #ident<#(#params),*>>);
 
     quote! {
@@ -118,6 +129,8 @@ pub fn store(s: Structure) -> TokenStream {
             const MODULE: &'static str = MODULE;
             const FIELD: &'static str = #store_name;
             type Returns = #store_ret;
+            const KEY_TYPES: Option<&'static [&'static str]> = Some(&[#(#key_types),*]);
+            const RETURN_TYPE: Option<&'static str> = Some(#return_type);
 
             fn prefix(
                 metadata: &#subxt::Metadata,
@@ -140,6 +153,29 @@ pub fn store(s: Structure) -> TokenStream {
             }
         }
 
+        impl#generics #subxt::StoreKey<T> for #ident<#(#params),*> {
+            type Key = #key_ty;
+
+            fn decode_key(
+                metadata: &#subxt::Metadata,
+                key: &#subxt::sp_core::storage::StorageKey,
+            ) -> Result<Self::Key, #subxt::MetadataError> {
+                metadata
+                    .module(Self::MODULE)?
+                    .storage(Self::FIELD)?
+                    .#store_ty#store_ty_params()?
+                    .decode_key(key)
+            }
+        }
+
+        impl#generics #subxt::CheckCompatibility<T> for #ident<#(#params),*> {
+            fn check_compatibility(
+                metadata: &#subxt::Metadata,
+            ) -> Vec<#subxt::Incompatibility> {
+                #subxt::check_store::<T, Self>(metadata)
+            }
+        }
+
         /// Store extension trait.
         pub trait #store_trait<T: #subxt::Runtime + #module> {
             /// Retrieve the store element.
@@ -194,6 +230,8 @@ mod tests {
                 const MODULE: &'static str = MODULE;
                 const FIELD: &'static str = "Account";
                 type Returns = AccountData<T::Balance>;
+                const KEY_TYPES: Option<&'static [&'static str]> = Some(&["< T as System > :: AccountId"]);
+                const RETURN_TYPE: Option<&'static str> = Some("AccountData < T :: Balance >");
 
                 fn prefix(
                     metadata: &substrate_subxt::Metadata,
@@ -216,6 +254,29 @@ mod tests {
                 }
             }
 
+            impl<'a, T: Balances> substrate_subxt::StoreKey<T> for AccountStore<'a, T> {
+                type Key = <T as System>::AccountId;
+
+                fn decode_key(
+                    metadata: &substrate_subxt::Metadata,
+                    key: &substrate_subxt::sp_core::storage::StorageKey,
+                ) -> Result<Self::Key, substrate_subxt::MetadataError> {
+                    metadata
+                        .module(Self::MODULE)?
+                        .storage(Self::FIELD)?
+                        .map::< <T as System>::AccountId>()?
+                        .decode_key(key)
+                }
+            }
+
+            impl<'a, T: Balances> substrate_subxt::CheckCompatibility<T> for AccountStore<'a, T> {
+                fn check_compatibility(
+                    metadata: &substrate_subxt::Metadata,
+                ) -> Vec<substrate_subxt::Incompatibility> {
+                    substrate_subxt::check_store::<T, Self>(metadata)
+                }
+            }
+
             /// Store extension trait.
             pub trait AccountStoreExt<T: substrate_subxt::Runtime + Balances> {
                 /// Retrieve the store element.
@@ -72,6 +72,46 @@ pub fn fields(bindings: &[&BindingInfo<'_>]) -> Vec<Field> {
         .collect()
 }
 
+/// Returns the type a reference refers to, or the type itself.
+pub fn owned_type(ty: &syn::Type) -> &syn::Type {
+    match ty {
+        syn::Type::Reference(reference) => owned_type(&reference.elem),
+        ty => ty,
+    }
+}
+
+/// Returns the name of a type as it would appear in the metadata, without references.
+pub fn type_name(ty: &syn::Type) -> String {
+    match ty {
+        syn::Type::Reference(reference) => type_name(&reference.elem),
+        ty => quote!(#ty).to_string(),
+    }
+}
+
+/// Returns the type names of the encoded fields, skipping the `PhantomData<T>` marker.
+///
+/// Fields with a `#[codec(compact)]` attribute are wrapped in `Compact`.
+pub fn arg_types(bindings: &[&BindingInfo<'_>]) -> Vec<String> {
+    bindings
+        .iter()
+        .map(|bi| bi.ast())
+        .filter(|field| {
+            let ty = &field.ty;
+            quote!(#ty).to_string() != quote!(PhantomData<T>).to_string()
+        })
+        .map(|field| {
+            let compact = field.attrs.iter().any(|attr| {
+                attr.path.is_ident("codec") && attr.tokens.to_string().contains("compact")
+            });
+            if compact {
+                format!("Compact<{}>", type_name(&field.ty))
+            } else {
+                type_name(&field.ty)
+            }
+        })
+        .collect()
+}
+
 pub fn marker_field(fields: &[Field]) -> Option<syn::Ident> {
     fields
         .iter()
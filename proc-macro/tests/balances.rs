@@ -32,6 +32,7 @@ use substrate_subxt::{
     },
     system::{
         System,
+        SystemCallEncoder,
         SystemEventsDecoder,
     },
     ClientBuilder,
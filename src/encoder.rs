@@ -0,0 +1,617 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of substrate-subxt.
+//
+// subxt is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// subxt is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-subxt.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Encoding of calls from dynamic values.
+
+use codec::{
+    Compact,
+    Decode,
+    Encode,
+};
+use std::{
+    collections::HashMap,
+    marker::PhantomData,
+};
+
+use crate::{
+    error::Error,
+    events::{
+        default_type_sizes,
+        TypeDecoder,
+    },
+    metadata::{
+        is_builtin_generic,
+        ConversionError,
+        EventArg,
+        Metadata,
+    },
+    registry::{
+        TypeDef,
+        TypeRegistry,
+    },
+    value::Value,
+    Encoded,
+    System,
+};
+
+/// Error encoding a call from dynamic values.
+#[derive(Debug, thiserror::Error)]
+pub enum EncodeError {
+    /// The number of values does not match the number of call arguments.
+    #[error("Call {0} expects {1} arguments, got {2}")]
+    ArgumentCount(String, usize, usize),
+    /// The type of an argument could not be parsed.
+    #[error("Invalid type of argument {0}: {1}")]
+    InvalidType(String, ConversionError),
+    /// A value does not match the type of its argument.
+    #[error("Argument {0} expects a value of type {1}, got {2:?}")]
+    TypeMismatch(String, String, Value),
+    /// Type size unavailable.
+    #[error("Type size unavailable while encoding argument {0}: {1}")]
+    TypeSizeUnavailable(String, String),
+}
+
+/// Encodes calls from `Value`s, checking them against the call arguments in the metadata.
+///
+/// Integers are encoded with the width of their argument type. Byte values of registered
+/// types must decode as exactly one value of the type, e.g. the `Source` of a `transfer`
+/// is the encoded address of the runtime, which `Client::call_encoder` registers.
+#[derive(Debug)]
+pub struct CallEncoder<T> {
+    metadata: Metadata,
+    type_decoders: HashMap<String, TypeDecoder>,
+    type_defs: HashMap<String, TypeDef>,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T: System> CallEncoder<T> {
+    /// Creates a new `CallEncoder`.
+    pub fn new(metadata: Metadata) -> Self {
+        let mut encoder = Self {
+            metadata,
+            type_decoders: HashMap::new(),
+            type_defs: HashMap::new(),
+            marker: PhantomData,
+        };
+        for (name, size) in default_type_sizes::<T>() {
+            encoder
+                .type_decoders
+                .insert(name.to_string(), TypeDecoder::fixed(size));
+        }
+        encoder
+    }
+
+    /// Register a type with a fixed encoded size.
+    ///
+    /// The size is taken from the encoding of `U::default()`.
+    pub fn register_type_size<U>(&mut self, name: &str) -> usize
+    where
+        U: Default + Encode,
+    {
+        let size = U::default().encode().len();
+        self.type_decoders
+            .insert(name.to_string(), TypeDecoder::fixed(size));
+        size
+    }
+
+    /// Register a type by its decoding function.
+    ///
+    /// Values of the type are checked by decoding them, so this works for types with a
+    /// variable encoded length like addresses.
+    pub fn register_type_decoder<U>(&mut self, name: &str)
+    where
+        U: Decode + 'static,
+    {
+        self.type_decoders
+            .insert(name.to_string(), TypeDecoder::new::<U>());
+    }
+
+    /// Register the type definitions of a type registry for a spec version.
+    ///
+    /// Type definitions take precedence over registered type sizes.
+    pub fn register_type_registry(&mut self, registry: &TypeRegistry, spec_version: u32) {
+        self.type_defs.extend(registry.types(spec_version));
+    }
+
+    /// Encodes a call with one value per argument.
+    pub fn encode_call(
+        &self,
+        module: &str,
+        call: &str,
+        args: &[Value],
+    ) -> Result<Encoded, Error> {
+        let module = self.metadata.module_with_calls(module)?;
+        let call = module.call_metadata(call)?;
+        if call.arguments().len() != args.len() {
+            return Err(EncodeError::ArgumentCount(
+                call.name().to_string(),
+                call.arguments().len(),
+                args.len(),
+            )
+            .into())
+        }
+        let mut bytes = vec![module.index(), call.index()];
+        for (arg, value) in call.arguments().iter().zip(args) {
+            let ty = arg
+                .arg_type()
+                .map_err(|err| EncodeError::InvalidType(arg.name().to_string(), err))?;
+            self.encode_value(arg.name(), &ty, value, &mut bytes)?;
+        }
+        Ok(Encoded(bytes))
+    }
+
+    fn encode_value(
+        &self,
+        arg: &str,
+        ty: &EventArg,
+        value: &Value,
+        out: &mut Vec<u8>,
+    ) -> Result<(), EncodeError> {
+        let mismatch =
+            || EncodeError::TypeMismatch(arg.to_string(), ty.to_string(), value.clone());
+        match (ty, value) {
+            (EventArg::Primitive(name), _) => {
+                self.encode_primitive(arg, name, value, out)
+            }
+            (EventArg::Vec(inner), Value::Bytes(bytes)) if is_u8(inner) => {
+                bytes.encode_to(out);
+                Ok(())
+            }
+            (EventArg::Vec(inner), Value::Sequence(values)) => {
+                self.encode_sequence(arg, inner, values, out)
+            }
+            (EventArg::Array(inner, len), Value::Bytes(bytes))
+                if is_u8(inner) && bytes.len() == *len =>
+            {
+                out.extend(bytes);
+                Ok(())
+            }
+            (EventArg::Array(inner, len), Value::Sequence(values))
+                if values.len() == *len =>
+            {
+                for value in values {
+                    self.encode_value(arg, inner, value, out)?;
+                }
+                Ok(())
+            }
+            (EventArg::Option(_), Value::Option(None)) => {
+                out.push(0);
+                Ok(())
+            }
+            (EventArg::Option(inner), Value::Option(Some(value))) => {
+                out.push(1);
+                self.encode_value(arg, inner, value, out)
+            }
+            (EventArg::Tuple(types), Value::Composite(values))
+                if types.len() == values.len() =>
+            {
+                for (ty, value) in types.iter().zip(values) {
+                    self.encode_value(arg, ty, value, out)?;
+                }
+                Ok(())
+            }
+            (EventArg::Compact(inner), Value::UInt(n)) => {
+                let fits = match &**inner {
+                    EventArg::Primitive(name) => {
+                        uint_width(name).map(|width| fits_uint(*n, width))
+                    }
+                    _ => None,
+                };
+                if fits == Some(false) {
+                    return Err(mismatch())
+                }
+                Compact(*n).encode_to(out);
+                Ok(())
+            }
+            (EventArg::Map(key, val), Value::Sequence(entries)) => {
+                Compact(entries.len() as u32).encode_to(out);
+                for entry in entries {
+                    match entry {
+                        Value::Composite(pair) if pair.len() == 2 => {
+                            self.encode_value(arg, key, &pair[0], out)?;
+                            self.encode_value(arg, val, &pair[1], out)?;
+                        }
+                        _ => return Err(mismatch()),
+                    }
+                }
+                Ok(())
+            }
+            (EventArg::Generic(name, params), _) => {
+                match (name.as_str(), params.as_slice(), value) {
+                    ("Box", [inner], _) => self.encode_value(arg, inner, value, out),
+                    ("BTreeSet", [inner], Value::Sequence(values))
+                    | ("VecDeque", [inner], Value::Sequence(values)) => {
+                        self.encode_sequence(arg, inner, values, out)
+                    }
+                    ("Result", [ok, err], Value::Variant(variant, value)) => {
+                        match variant.as_str() {
+                            "Ok" => {
+                                out.push(0);
+                                self.encode_value(arg, ok, value, out)
+                            }
+                            "Err" => {
+                                out.push(1);
+                                self.encode_value(arg, err, value, out)
+                            }
+                            _ => Err(mismatch()),
+                        }
+                    }
+                    (name, params, _) if !is_builtin_generic(name, params.len()) => {
+                        self.encode_primitive(arg, &ty.to_string(), value, out)
+                    }
+                    _ => Err(mismatch()),
+                }
+            }
+            _ => Err(mismatch()),
+        }
+    }
+
+    fn encode_sequence(
+        &self,
+        arg: &str,
+        ty: &EventArg,
+        values: &[Value],
+        out: &mut Vec<u8>,
+    ) -> Result<(), EncodeError> {
+        Compact(values.len() as u32).encode_to(out);
+        for value in values {
+            self.encode_value(arg, ty, value, out)?;
+        }
+        Ok(())
+    }
+
+    fn encode_primitive(
+        &self,
+        arg: &str,
+        name: &str,
+        value: &Value,
+        out: &mut Vec<u8>,
+    ) -> Result<(), EncodeError> {
+        if let Some(def) = self.type_defs.get(name) {
+            return self.encode_type_def(arg, name, def, value, out)
+        }
+        let mismatch = || {
+            EncodeError::TypeMismatch(arg.to_string(), name.to_string(), value.clone())
+        };
+        match value {
+            Value::Bool(b) if name == "bool" => {
+                b.encode_to(out);
+                return Ok(())
+            }
+            Value::Int(n) => {
+                return match int_width(name) {
+                    Some(width) if fits_int(*n, width) => {
+                        out.extend(&n.to_le_bytes()[..width]);
+                        Ok(())
+                    }
+                    _ => Err(mismatch()),
+                }
+            }
+            _ => {}
+        }
+        let fixed;
+        let decoder = match (self.type_decoders.get(name), uint_width(name)) {
+            (Some(decoder), _) => decoder,
+            (None, Some(width)) => {
+                fixed = TypeDecoder::fixed(width);
+                &fixed
+            }
+            (None, None) => {
+                return match value {
+                    Value::UInt(_)
+                    | Value::Bytes(_)
+                    | Value::AccountId(_)
+                    | Value::Hash(_) => {
+                        Err(EncodeError::TypeSizeUnavailable(
+                            arg.to_string(),
+                            name.to_string(),
+                        ))
+                    }
+                    _ => Err(mismatch()),
+                }
+            }
+        };
+        match value {
+            Value::UInt(n) => {
+                // an integer has the smallest width a value of the type decodes from
+                let bytes = n.to_le_bytes();
+                let width = [1, 2, 4, 8, 16]
+                    .iter()
+                    .find(|width| {
+                        fits_uint(*n, **width) && decoder.decodes(&bytes[..**width])
+                    })
+                    .ok_or_else(mismatch)?;
+                out.extend(&bytes[..*width]);
+                Ok(())
+            }
+            Value::Bytes(bytes) | Value::AccountId(bytes) | Value::Hash(bytes)
+                if decoder.decodes(bytes) =>
+            {
+                out.extend(bytes);
+                Ok(())
+            }
+            _ => Err(mismatch()),
+        }
+    }
+
+    fn encode_type_def(
+        &self,
+        arg: &str,
+        name: &str,
+        def: &TypeDef,
+        value: &Value,
+        out: &mut Vec<u8>,
+    ) -> Result<(), EncodeError> {
+        let mismatch = || {
+            EncodeError::TypeMismatch(arg.to_string(), name.to_string(), value.clone())
+        };
+        match (def, value) {
+            (TypeDef::Alias(ty), _) => self.encode_value(arg, ty, value, out),
+            (TypeDef::Struct(fields), Value::Composite(values))
+                if fields.len() == values.len() =>
+            {
+                for ((_, ty), value) in fields.iter().zip(values) {
+                    self.encode_value(arg, ty, value, out)?;
+                }
+                Ok(())
+            }
+            (TypeDef::Enum(variants), Value::Variant(variant, value)) => {
+                let index = variants
+                    .iter()
+                    .position(|(name, _)| name == variant)
+                    .ok_or_else(mismatch)?;
+                out.push(index as u8);
+                self.encode_value(arg, &variants[index].1, value, out)
+            }
+            _ => Err(mismatch()),
+        }
+    }
+}
+
+fn is_u8(ty: &EventArg) -> bool {
+    matches!(ty, EventArg::Primitive(name) if name == "u8")
+}
+
+fn uint_width(name: &str) -> Option<usize> {
+    match name {
+        "u8" => Some(1),
+        "u16" => Some(2),
+        "u32" => Some(4),
+        "u64" => Some(8),
+        "u128" => Some(16),
+        _ => None,
+    }
+}
+
+fn int_width(name: &str) -> Option<usize> {
+    match name {
+        "i8" => Some(1),
+        "i16" => Some(2),
+        "i32" => Some(4),
+        "i64" => Some(8),
+        "i128" => Some(16),
+        _ => None,
+    }
+}
+
+fn fits_uint(n: u128, width: usize) -> bool {
+    width >= 16 || n >> (8 * width) == 0
+}
+
+fn fits_int(n: i128, width: usize) -> bool {
+    if width >= 16 {
+        return true
+    }
+    let bits = 8 * width as u32;
+    let min = -(1i128 << (bits - 1));
+    let max = (1i128 << (bits - 1)) - 1;
+    min <= n && n <= max
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame::balances::BalancesCallEncoder;
+    use frame_metadata::{
+        DecodeDifferent,
+        ExtrinsicMetadata,
+        FunctionArgumentMetadata,
+        FunctionMetadata,
+        ModuleMetadata,
+        RuntimeMetadata,
+        RuntimeMetadataPrefixed,
+        RuntimeMetadataV12,
+        META_RESERVED,
+    };
+    use std::convert::TryFrom;
+
+    type TestRuntime = crate::NodeTemplateRuntime;
+
+    fn encoder() -> CallEncoder<TestRuntime> {
+        let call = |name: &str, args: &[(&str, &str)]| {
+            FunctionMetadata {
+                name: DecodeDifferent::Decoded(name.to_string()),
+                arguments: DecodeDifferent::Decoded(
+                    args.iter()
+                        .map(|(name, ty)| {
+                            FunctionArgumentMetadata {
+                                name: DecodeDifferent::Decoded(name.to_string()),
+                                ty: DecodeDifferent::Decoded(ty.to_string()),
+                            }
+                        })
+                        .collect(),
+                ),
+                documentation: DecodeDifferent::Decoded(vec![]),
+            }
+        };
+        let metadata = Metadata::try_from(RuntimeMetadataPrefixed(
+            META_RESERVED,
+            RuntimeMetadata::V12(RuntimeMetadataV12 {
+                modules: DecodeDifferent::Decoded(vec![ModuleMetadata {
+                    name: DecodeDifferent::Decoded("Balances".to_string()),
+                    storage: None,
+                    calls: Some(DecodeDifferent::Decoded(vec![
+                        call(
+                            "transfer",
+                            &[
+                                ("dest", "<T::Lookup as StaticLookup>::Source"),
+                                ("value", "Compact<T::Balance>"),
+                            ],
+                        ),
+                        call("set_fee", &[("fee", "T::Balance")]),
+                        call(
+                            "remark",
+                            &[
+                                ("at", "T::BlockNumber"),
+                                ("remark", "Vec<u8>"),
+                                ("tags", "Option<Vec<(u16, bool)>>"),
+                            ],
+                        ),
+                    ])),
+                    event: None,
+                    constants: DecodeDifferent::Decoded(vec![]),
+                    errors: DecodeDifferent::Decoded(vec![]),
+                    index: 5,
+                }]),
+                extrinsic: ExtrinsicMetadata {
+                    version: 4,
+                    signed_extensions: vec![],
+                },
+            }),
+        ))
+        .unwrap();
+        CallEncoder::new(metadata)
+    }
+
+    #[test]
+    fn test_encode_call() {
+        let mut encoder = encoder();
+        encoder.register_type_decoder::<<TestRuntime as System>::Address>("Source");
+
+        let dest = [1u8; 32];
+        let call = encoder
+            .encode_call(
+                "Balances",
+                "transfer",
+                &[Value::AccountId(dest.to_vec()), Value::UInt(1_000)],
+            )
+            .unwrap();
+        let mut expected = vec![5, 0];
+        expected.extend((dest, Compact(1_000u128)).encode());
+        assert_eq!(call.0, expected);
+
+        let call = encoder
+            .encode_call(
+                "Balances",
+                "remark",
+                &[
+                    Value::UInt(7),
+                    Value::Bytes(b"hi".to_vec()),
+                    Value::Option(Some(Box::new(Value::Sequence(vec![
+                        Value::Composite(vec![Value::UInt(2), Value::Bool(true)]),
+                    ])))),
+                ],
+            )
+            .unwrap();
+        let mut expected = vec![5, 2];
+        expected.extend((7u32, b"hi".to_vec(), Some(vec![(2u16, true)])).encode());
+        assert_eq!(call.0, expected);
+    }
+
+    #[test]
+    fn test_encode_call_errors() {
+        let mut encoder = encoder();
+
+        let result = encoder.encode_call("Balances", "transfer", &[Value::UInt(1)]);
+        assert!(matches!(
+            result,
+            Err(Error::Encode(EncodeError::ArgumentCount(_, 2, 1)))
+        ));
+
+        // the size of an address is unknown until its type is registered
+        let result = encoder.encode_call(
+            "Balances",
+            "transfer",
+            &[Value::AccountId(vec![0; 32]), Value::UInt(1)],
+        );
+        assert!(matches!(
+            result,
+            Err(Error::Encode(EncodeError::TypeSizeUnavailable(arg, _))) if arg == "dest"
+        ));
+
+        encoder.register_type_decoder::<<TestRuntime as System>::Address>("Source");
+        let result = encoder.encode_call(
+            "Balances",
+            "transfer",
+            &[Value::AccountId(vec![0; 32]), Value::Bool(true)],
+        );
+        assert!(matches!(
+            result,
+            Err(Error::Encode(EncodeError::TypeMismatch(arg, _, _))) if arg == "value"
+        ));
+
+        // block numbers of the test runtime are u32
+        let result = encoder.encode_call(
+            "Balances",
+            "remark",
+            &[
+                Value::UInt(u128::from(u32::MAX) + 1),
+                Value::Bytes(vec![]),
+                Value::Option(None),
+            ],
+        );
+        assert!(matches!(
+            result,
+            Err(Error::Encode(EncodeError::TypeMismatch(arg, _, _))) if arg == "at"
+        ));
+
+        // an address has to decode as the address of the runtime
+        let result = encoder.encode_call(
+            "Balances",
+            "transfer",
+            &[Value::AccountId(vec![0; 31]), Value::UInt(1)],
+        );
+        assert!(matches!(
+            result,
+            Err(Error::Encode(EncodeError::TypeMismatch(arg, _, _))) if arg == "dest"
+        ));
+
+        let result = encoder.encode_call("Balances", "burn", &[]);
+        assert!(matches!(
+            result,
+            Err(Error::Metadata(crate::MetadataError::CallNameNotFound(_)))
+        ));
+    }
+
+    #[test]
+    fn test_encode_balance() {
+        let mut encoder = encoder();
+
+        let result = encoder.encode_call("Balances", "set_fee", &[Value::UInt(3)]);
+        assert!(matches!(
+            result,
+            Err(Error::Encode(EncodeError::TypeSizeUnavailable(arg, _))) if arg == "fee"
+        ));
+
+        encoder.with_balances();
+        let call = encoder
+            .encode_call("Balances", "set_fee", &[Value::UInt(3)])
+            .unwrap();
+        let mut expected = vec![5, 1];
+        expected.extend(3u128.encode());
+        assert_eq!(call.0, expected);
+    }
+}
@@ -25,9 +25,15 @@ use sp_runtime::{
 };
 use thiserror::Error;
 
-use crate::metadata::{
-    Metadata,
-    MetadataError,
+use crate::{
+    encoder::EncodeError,
+    events::UndecodedEvents,
+    metadata::{
+        Incompatibility,
+        Metadata,
+        MetadataError,
+    },
+    registry::TypeRegistryError,
 };
 
 /// Error enum.
@@ -57,12 +63,33 @@ pub enum Error {
     /// Metadata error.
     #[error("Metadata error: {0}")]
     Metadata(#[from] MetadataError),
+    /// Type registry error.
+    #[error("Type registry error: {0}")]
+    TypeRegistry(#[from] TypeRegistryError),
+    /// Call encoding error.
+    #[error("Call encoding error: {0}")]
+    Encode(#[from] EncodeError),
     /// Type size unavailable.
     #[error("Type size unavailable while decoding event: {0:?}")]
     TypeSizeUnavailable(String),
+    /// Events which could not be decoded.
+    #[error("Undecoded events: {0}")]
+    Undecoded(Box<UndecodedEvents>),
+    /// Compiled types do not match the metadata.
+    #[error(
+        "Incompatible with the runtime metadata: {}",
+        .0.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+    )]
+    Incompatible(Vec<Incompatibility>),
     /// Runtime error.
     #[error("Runtime error: {0}")]
     Runtime(#[from] RuntimeError),
+    /// The tip does not fit the balance type of the runtime.
+    #[error("Tip {0} does not fit the balance type of the runtime")]
+    TipOverflow(u128),
+    /// The client was built without a node to send requests to.
+    #[error("Client is offline, set a url or client to send requests to a node")]
+    Offline,
     /// Other error.
     #[error("Other error: {0}")]
     Other(String),
@@ -29,13 +29,16 @@ use sp_runtime::{
 };
 use std::{
     collections::{
+        BTreeMap,
         HashMap,
         HashSet,
     },
+    fmt,
     marker::{
         PhantomData,
         Send,
     },
+    sync::Arc,
 };
 
 use crate::{
@@ -44,9 +47,19 @@ use crate::{
         RuntimeError,
     },
     metadata::{
+        is_builtin_generic,
         EventArg,
         Metadata,
     },
+    registry::{
+        TypeDef,
+        TypeRegistry,
+    },
+    value::{
+        PrimitiveKind,
+        Value,
+    },
+    Event,
     Phase,
     System,
 };
@@ -71,11 +84,89 @@ impl std::fmt::Debug for RawEvent {
     }
 }
 
+/// An event record of a block.
+#[derive(Debug)]
+pub struct EventRecord<T: System> {
+    /// The index of the record in the events of the block
+    pub index: u32,
+    /// The phase of the block execution in which the event was emitted
+    pub phase: Phase,
+    /// The Event
+    pub event: RawEvent,
+    /// The topics the Event was deposited with
+    pub topics: Vec<T::Hash>,
+}
+
+/// Consumes exactly one encoded value of a registered type from the input.
+#[derive(Clone)]
+pub(crate) struct TypeDecoder(
+    Arc<dyn Fn(&mut &[u8]) -> Result<(), codec::Error> + Send + Sync>,
+);
+
+impl TypeDecoder {
+    pub(crate) fn new<U: Decode>() -> Self {
+        Self(Arc::new(|input: &mut &[u8]| U::decode(input).map(|_| ())))
+    }
+
+    pub(crate) fn fixed(size: usize) -> Self {
+        Self(Arc::new(move |input: &mut &[u8]| {
+            let mut buf = vec![0; size];
+            input.read(&mut buf)
+        }))
+    }
+
+    /// Returns whether the bytes are exactly the encoding of one value.
+    pub(crate) fn decodes(&self, bytes: &[u8]) -> bool {
+        let input = &mut &bytes[..];
+        (self.0)(input).is_ok() && input.is_empty()
+    }
+}
+
+impl fmt::Debug for TypeDecoder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("TypeDecoder")
+    }
+}
+
+/// Returns the names and encoded sizes of the types with a fixed size which are known
+/// without registering them, for decoding events and encoding calls.
+pub(crate) fn default_type_sizes<T: System>() -> Vec<(&'static str, usize)> {
+    fn size<U: Default + Encode>() -> usize {
+        U::default().encode().len()
+    }
+    vec![
+        ("PhantomData", size::<()>()),
+        ("DispatchInfo", size::<DispatchInfo>()),
+        ("bool", size::<bool>()),
+        ("ReferendumIndex", size::<u32>()),
+        ("Kind", size::<[u8; 16]>()),
+        ("AuthorityId", size::<[u8; 32]>()),
+        ("u8", size::<u8>()),
+        ("u32", size::<u32>()),
+        ("u64", size::<u64>()),
+        ("u128", size::<u128>()),
+        ("AccountIndex", size::<u32>()),
+        ("SessionIndex", size::<u32>()),
+        ("PropIndex", size::<u32>()),
+        ("ProposalIndex", size::<u32>()),
+        ("AuthorityIndex", size::<u32>()),
+        ("AuthorityWeight", size::<u64>()),
+        ("MemberCount", size::<u32>()),
+        ("AccountId", size::<T::AccountId>()),
+        ("BlockNumber", size::<T::BlockNumber>()),
+        ("Hash", size::<T::Hash>()),
+        ("Index", size::<T::Index>()),
+        ("VoteThreshold", size::<u8>()),
+    ]
+}
+
 /// Events decoder.
 #[derive(Debug)]
 pub struct EventsDecoder<T> {
-    metadata: Metadata,
-    type_sizes: HashMap<String, usize>,
+    metadata: Arc<Metadata>,
+    type_decoders: HashMap<String, TypeDecoder>,
+    type_defs: HashMap<String, TypeDef>,
+    lenient: bool,
     marker: PhantomData<fn() -> T>,
 }
 
@@ -83,53 +174,97 @@ impl<T: System> EventsDecoder<T> {
     /// Creates a new `EventsDecoder`.
     pub fn new(metadata: Metadata) -> Self {
         let mut decoder = Self {
-            metadata,
-            type_sizes: HashMap::new(),
+            metadata: Arc::new(metadata),
+            type_decoders: HashMap::new(),
+            type_defs: HashMap::new(),
+            lenient: false,
             marker: PhantomData,
         };
         // register default event arg type sizes for dynamic decoding of events
-        decoder.register_type_size::<()>("PhantomData");
-        decoder.register_type_size::<DispatchInfo>("DispatchInfo");
-        decoder.register_type_size::<bool>("bool");
-        decoder.register_type_size::<u32>("ReferendumIndex");
-        decoder.register_type_size::<[u8; 16]>("Kind");
-        decoder.register_type_size::<[u8; 32]>("AuthorityId");
-        decoder.register_type_size::<u8>("u8");
-        decoder.register_type_size::<u32>("u32");
-        decoder.register_type_size::<u64>("u64");
-        decoder.register_type_size::<u128>("u128");
-        decoder.register_type_size::<u32>("AccountIndex");
-        decoder.register_type_size::<u32>("SessionIndex");
-        decoder.register_type_size::<u32>("PropIndex");
-        decoder.register_type_size::<u32>("ProposalIndex");
-        decoder.register_type_size::<u32>("AuthorityIndex");
-        decoder.register_type_size::<u64>("AuthorityWeight");
-        decoder.register_type_size::<u32>("MemberCount");
-        decoder.register_type_size::<T::AccountId>("AccountId");
-        decoder.register_type_size::<T::BlockNumber>("BlockNumber");
-        decoder.register_type_size::<T::Hash>("Hash");
-        decoder.register_type_size::<u8>("VoteThreshold");
+        for (name, size) in default_type_sizes::<T>() {
+            decoder
+                .type_decoders
+                .insert(name.to_string(), TypeDecoder::fixed(size));
+        }
         decoder
     }
 
-    /// Register a type.
+    /// Register a type with a fixed encoded size.
+    ///
+    /// The size is taken from the encoding of `U::default()`, so this must only be used for
+    /// types whose encoded length does not depend on the value. Use
+    /// `register_type_decoder` for everything else.
     pub fn register_type_size<U>(&mut self, name: &str) -> usize
     where
         U: Default + Codec + Send + 'static,
     {
         let size = U::default().encode().len();
-        self.type_sizes.insert(name.to_string(), size);
+        self.type_decoders
+            .insert(name.to_string(), TypeDecoder::fixed(size));
         size
     }
 
+    /// Register a type by its decoding function.
+    ///
+    /// Decoding the type consumes exactly the bytes of one encoded value, so this works for
+    /// types with a variable encoded length like enums with payloads, vectors or maps.
+    pub fn register_type_decoder<U>(&mut self, name: &str)
+    where
+        U: Decode + 'static,
+    {
+        self.type_decoders
+            .insert(name.to_string(), TypeDecoder::new::<U>());
+    }
+
+    /// Register the type definitions of a type registry for a spec version.
+    ///
+    /// Type definitions take precedence over registered type sizes and decoders.
+    pub fn register_type_registry(&mut self, registry: &TypeRegistry, spec_version: u32) {
+        self.type_defs.extend(registry.types(spec_version));
+    }
+
+    /// Returns a decoder for the events of another runtime version.
+    ///
+    /// The registered type sizes and decoders are kept, the type definitions of the
+    /// registry for the spec version take precedence over the registered ones.
+    pub fn for_runtime(
+        &self,
+        metadata: Arc<Metadata>,
+        registry: &TypeRegistry,
+        spec_version: u32,
+    ) -> Self {
+        let mut type_defs = self.type_defs.clone();
+        type_defs.extend(registry.types(spec_version));
+        Self {
+            metadata,
+            type_decoders: self.type_decoders.clone(),
+            type_defs,
+            lenient: self.lenient,
+            marker: PhantomData,
+        }
+    }
+
+    fn is_registered(&self, name: &str) -> bool {
+        self.type_defs.contains_key(name) || self.type_decoders.contains_key(name)
+    }
+
     /// Check missing type sizes.
     pub fn check_missing_type_sizes(&self) {
         let mut missing = HashSet::new();
+        for (name, def) in &self.type_defs {
+            for arg in def.args() {
+                for primitive in arg.primitives() {
+                    if !self.is_registered(&primitive) {
+                        missing.insert(format!("{}::{}", name, primitive));
+                    }
+                }
+            }
+        }
         for module in self.metadata.modules_with_events() {
             for event in module.events() {
                 for arg in event.arguments() {
                     for primitive in arg.primitives() {
-                        if !self.type_sizes.contains_key(&primitive) {
+                        if !self.is_registered(&primitive) {
                             missing.insert(format!(
                                 "{}::{}::{}",
                                 module.name(),
@@ -150,108 +285,443 @@ impl<T: System> EventsDecoder<T> {
         }
     }
 
-    fn decode_raw_bytes<I: Input, W: Output>(
+    fn decode_raw_bytes<W: Output>(
         &self,
         args: &[EventArg],
-        input: &mut I,
+        input: &mut &[u8],
         output: &mut W,
         errors: &mut Vec<RuntimeError>,
     ) -> Result<(), Error> {
         for arg in args {
             match arg {
-                EventArg::Vec(arg) => {
-                    let len = <Compact<u32>>::decode(input)?;
-                    len.encode_to(output);
-                    for _ in 0..len.0 {
-                        self.decode_raw_bytes(&[*arg.clone()], input, output, errors)?
+                // since the input may contain any number of args we propagate
+                // runtime errors to the caller for handling
+                EventArg::Primitive(name) if name == "DispatchResult" => {
+                    if let Err(error) = DispatchResult::decode(input)? {
+                        errors.push(RuntimeError::from_dispatch(&self.metadata, error)?);
                     }
                 }
-                EventArg::Option(arg) => {
-                    match input.read_byte()? {
-                        0 => output.push_byte(0),
-                        1 => {
-                            output.push_byte(1);
-                            self.decode_raw_bytes(&[*arg.clone()], input, output, errors)?
-                        }
-                        _ => {
-                            return Err(Error::Other(
-                                "unexpected first byte decoding Option".into(),
-                            ))
-                        }
+                EventArg::Primitive(name) if name == "DispatchError" => {
+                    let error = DispatchError::decode(input)?;
+                    errors.push(RuntimeError::from_dispatch(&self.metadata, error)?);
+                }
+                _ => self.decode_arg_bytes(arg, input, output)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn decode_arg_bytes<W: Output>(
+        &self,
+        arg: &EventArg,
+        input: &mut &[u8],
+        output: &mut W,
+    ) -> Result<(), Error> {
+        if let Some(def) = self.type_def(arg) {
+            return self.decode_type_def(def, input, output)
+        }
+        match arg {
+            EventArg::Vec(arg) => {
+                let len = <Compact<u32>>::decode(input)?;
+                len.encode_to(output);
+                for _ in 0..len.0 {
+                    self.decode_arg_bytes(arg, input, output)?
+                }
+            }
+            EventArg::Option(arg) => {
+                match input.read_byte()? {
+                    0 => output.push_byte(0),
+                    1 => {
+                        output.push_byte(1);
+                        self.decode_arg_bytes(arg, input, output)?
+                    }
+                    _ => {
+                        return Err(Error::Other(
+                            "unexpected first byte decoding Option".into(),
+                        ))
                     }
                 }
-                EventArg::Tuple(args) => {
-                    self.decode_raw_bytes(args, input, output, errors)?
+            }
+            EventArg::Tuple(args) => {
+                for arg in args {
+                    self.decode_arg_bytes(arg, input, output)?
                 }
-                EventArg::Primitive(name) => {
-                    let result = match name.as_str() {
-                        "DispatchResult" => DispatchResult::decode(input)?,
-                        "DispatchError" => Err(DispatchError::decode(input)?),
-                        _ => {
-                            if let Some(size) = self.type_sizes.get(name) {
-                                let mut buf = vec![0; *size];
-                                input.read(&mut buf)?;
-                                output.write(&buf);
-                                Ok(())
-                            } else {
-                                return Err(Error::TypeSizeUnavailable(name.to_owned()))
-                            }
+            }
+            EventArg::Array(arg, len) => {
+                for _ in 0..*len {
+                    self.decode_arg_bytes(arg, input, output)?
+                }
+            }
+            EventArg::Compact(_) => {
+                // the compact encoding of all unsigned integers is compatible,
+                // so the widest one consumes the right number of bytes
+                let start = *input;
+                <Compact<u128>>::decode(input)?;
+                output.write(consumed(start, input));
+            }
+            EventArg::Map(key, value) => {
+                let len = <Compact<u32>>::decode(input)?;
+                len.encode_to(output);
+                for _ in 0..len.0 {
+                    self.decode_arg_bytes(key, input, output)?;
+                    self.decode_arg_bytes(value, input, output)?
+                }
+            }
+            EventArg::Generic(name, params) => {
+                if let Some(decoder) = self.type_decoders.get(&arg.to_string()) {
+                    let start = *input;
+                    (decoder.0)(input)?;
+                    output.write(consumed(start, input));
+                } else if is_builtin_generic(name, params.len()) {
+                    self.decode_builtin_generic(name, params, input, output)?
+                } else {
+                    return Err(Error::TypeSizeUnavailable(arg.to_string()))
+                }
+            }
+            EventArg::Primitive(name) => {
+                let start = *input;
+                match name.as_str() {
+                    // nested dispatch results, e.g. an `Option<DispatchError>`, are
+                    // part of the event data
+                    "DispatchResult" => {
+                        DispatchResult::decode(input)?;
+                    }
+                    "DispatchError" => {
+                        DispatchError::decode(input)?;
+                    }
+                    _ => {
+                        if let Some(decoder) = self.type_decoders.get(name) {
+                            (decoder.0)(input)?;
+                        } else {
+                            return Err(Error::TypeSizeUnavailable(name.to_owned()))
                         }
-                    };
-                    if let Err(error) = result {
-                        // since the input may contain any number of args we propagate
-                        // runtime errors to the caller for handling
-                        errors.push(RuntimeError::from_dispatch(&self.metadata, error)?);
                     }
                 }
+                output.write(consumed(start, input));
             }
         }
         Ok(())
     }
 
+    fn decode_builtin_generic<W: Output>(
+        &self,
+        name: &str,
+        params: &[EventArg],
+        input: &mut &[u8],
+        output: &mut W,
+    ) -> Result<(), Error> {
+        match name {
+            "Result" => {
+                let variant = input.read_byte()?;
+                output.push_byte(variant);
+                match variant {
+                    0 => self.decode_arg_bytes(&params[0], input, output),
+                    1 => self.decode_arg_bytes(&params[1], input, output),
+                    _ => {
+                        Err(Error::Other("unexpected first byte decoding Result".into()))
+                    }
+                }
+            }
+            "BTreeSet" | "VecDeque" => {
+                self.decode_arg_bytes(
+                    &EventArg::Vec(Box::new(params[0].clone())),
+                    input,
+                    output,
+                )
+            }
+            _ => {
+                for param in params {
+                    self.decode_arg_bytes(param, input, output)?
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn type_def(&self, arg: &EventArg) -> Option<&TypeDef> {
+        if self.type_defs.is_empty() {
+            return None
+        }
+        match arg {
+            EventArg::Primitive(name) => self.type_defs.get(name),
+            EventArg::Generic(_, _) => self.type_defs.get(&arg.to_string()),
+            _ => None,
+        }
+    }
+
+    fn decode_type_def<W: Output>(
+        &self,
+        def: &TypeDef,
+        input: &mut &[u8],
+        output: &mut W,
+    ) -> Result<(), Error> {
+        match def {
+            TypeDef::Alias(arg) => self.decode_arg_bytes(arg, input, output),
+            TypeDef::Struct(fields) => {
+                for (_, arg) in fields {
+                    self.decode_arg_bytes(arg, input, output)?;
+                }
+                Ok(())
+            }
+            TypeDef::Enum(variants) => {
+                let index = input.read_byte()?;
+                output.push_byte(index);
+                let (_, arg) = enum_variant(variants, index)?;
+                self.decode_arg_bytes(arg, input, output)
+            }
+        }
+    }
+
+    /// Decodes the data of an event into a `Value` by walking the event arguments in the
+    /// metadata.
+    ///
+    /// Returns a `Value::Composite` with one value per event argument, except for the
+    /// `DispatchResult` and `DispatchError` arguments, which are not part of the event
+    /// data.
+    pub fn decode_value(&self, event: &RawEvent) -> Result<Value, Error> {
+        let event_metadata = self.metadata.event(&event.module, &event.variant)?;
+        let input = &mut &event.data[..];
+        let mut values = Vec::new();
+        for arg in event_metadata.arguments() {
+            if let EventArg::Primitive(name) = &arg {
+                if name == "DispatchResult" || name == "DispatchError" {
+                    continue
+                }
+            }
+            values.push(self.decode_arg_value(&arg, input)?);
+        }
+        if !input.is_empty() {
+            return Err(Error::Other(format!(
+                "{} bytes left after decoding event {}::{}",
+                input.len(),
+                event.module,
+                event.variant
+            )))
+        }
+        Ok(Value::Composite(values))
+    }
+
+    fn decode_arg_value(
+        &self,
+        arg: &EventArg,
+        input: &mut &[u8],
+    ) -> Result<Value, Error> {
+        if let Some(def) = self.type_def(arg) {
+            let value = match def {
+                TypeDef::Alias(arg) => self.decode_arg_value(arg, input)?,
+                TypeDef::Struct(fields) => {
+                    let mut values = Vec::new();
+                    for (_, arg) in fields {
+                        values.push(self.decode_arg_value(arg, input)?);
+                    }
+                    Value::Composite(values)
+                }
+                TypeDef::Enum(variants) => {
+                    let (name, arg) = enum_variant(variants, input.read_byte()?)?;
+                    let value = self.decode_arg_value(arg, input)?;
+                    Value::Variant(name.clone(), Box::new(value))
+                }
+            };
+            return Ok(value)
+        }
+        let value = match arg {
+            EventArg::Vec(arg) if **arg == EventArg::Primitive("u8".into()) => {
+                Value::Bytes(Vec::<u8>::decode(input)?)
+            }
+            EventArg::Vec(arg) => {
+                let len = <Compact<u32>>::decode(input)?;
+                let mut values = Vec::new();
+                for _ in 0..len.0 {
+                    values.push(self.decode_arg_value(arg, input)?);
+                }
+                Value::Sequence(values)
+            }
+            EventArg::Option(arg) => {
+                match input.read_byte()? {
+                    0 => Value::Option(None),
+                    1 => {
+                        Value::Option(Some(Box::new(self.decode_arg_value(arg, input)?)))
+                    }
+                    _ => {
+                        return Err(Error::Other(
+                            "unexpected first byte decoding Option".into(),
+                        ))
+                    }
+                }
+            }
+            EventArg::Tuple(args) => {
+                let mut values = Vec::new();
+                for arg in args {
+                    values.push(self.decode_arg_value(arg, input)?);
+                }
+                Value::Composite(values)
+            }
+            EventArg::Array(arg, len) if **arg == EventArg::Primitive("u8".into()) => {
+                let mut bytes = vec![0; *len];
+                input.read(&mut bytes)?;
+                Value::Bytes(bytes)
+            }
+            EventArg::Array(arg, len) => {
+                let mut values = Vec::new();
+                for _ in 0..*len {
+                    values.push(self.decode_arg_value(arg, input)?);
+                }
+                Value::Sequence(values)
+            }
+            EventArg::Compact(_) => Value::UInt(<Compact<u128>>::decode(input)?.0),
+            EventArg::Map(key, value) => {
+                let len = <Compact<u32>>::decode(input)?;
+                let mut values = Vec::new();
+                for _ in 0..len.0 {
+                    values.push(Value::Composite(vec![
+                        self.decode_arg_value(key, input)?,
+                        self.decode_arg_value(value, input)?,
+                    ]));
+                }
+                Value::Sequence(values)
+            }
+            EventArg::Generic(name, params) => {
+                if self.type_decoders.contains_key(&arg.to_string()) {
+                    let mut bytes = Vec::new();
+                    self.decode_arg_bytes(arg, input, &mut bytes)?;
+                    Value::Bytes(bytes)
+                } else {
+                    match (name.as_str(), &params[..]) {
+                        ("Result", [ok, err]) => {
+                            match input.read_byte()? {
+                                0 => {
+                                    let value = self.decode_arg_value(ok, input)?;
+                                    Value::Variant("Ok".into(), Box::new(value))
+                                }
+                                1 => {
+                                    let value = self.decode_arg_value(err, input)?;
+                                    Value::Variant("Err".into(), Box::new(value))
+                                }
+                                _ => {
+                                    return Err(Error::Other(
+                                        "unexpected first byte decoding Result".into(),
+                                    ))
+                                }
+                            }
+                        }
+                        ("BTreeSet", [arg]) | ("VecDeque", [arg]) => {
+                            self.decode_arg_value(
+                                &EventArg::Vec(Box::new(arg.clone())),
+                                input,
+                            )?
+                        }
+                        ("Box", [arg]) => self.decode_arg_value(arg, input)?,
+                        _ => return Err(Error::TypeSizeUnavailable(arg.to_string())),
+                    }
+                }
+            }
+            EventArg::Primitive(name) => {
+                match name.as_str() {
+                    "bool" => Value::Bool(bool::decode(input)?),
+                    "u8" => Value::UInt(u8::decode(input)?.into()),
+                    "u16" => Value::UInt(u16::decode(input)?.into()),
+                    "u32" => Value::UInt(u32::decode(input)?.into()),
+                    "u64" => Value::UInt(u64::decode(input)?.into()),
+                    "u128" => Value::UInt(u128::decode(input)?),
+                    "i8" => Value::Int(i8::decode(input)?.into()),
+                    "i16" => Value::Int(i16::decode(input)?.into()),
+                    "i32" => Value::Int(i32::decode(input)?.into()),
+                    "i64" => Value::Int(i64::decode(input)?.into()),
+                    "i128" => Value::Int(i128::decode(input)?),
+                    "DispatchResult" => {
+                        match DispatchResult::decode(input)? {
+                            Ok(()) => {
+                                Value::Variant(
+                                    "Ok".into(),
+                                    Box::new(Value::Composite(vec![])),
+                                )
+                            }
+                            Err(error) => {
+                                Value::Variant(
+                                    "Err".into(),
+                                    Box::new(dispatch_error_value(error)),
+                                )
+                            }
+                        }
+                    }
+                    "DispatchError" => {
+                        dispatch_error_value(DispatchError::decode(input)?)
+                    }
+                    _ => {
+                        let mut bytes = Vec::new();
+                        self.decode_arg_bytes(arg, input, &mut bytes)?;
+                        match PrimitiveKind::of(name) {
+                            Some(kind) => kind.to_value(&bytes),
+                            None => Value::Bytes(bytes),
+                        }
+                    }
+                }
+            }
+        };
+        Ok(value)
+    }
+
+    /// Enables or disables lenient decoding.
+    ///
+    /// In lenient mode `decode_events` does not fail when an event can not be decoded.
+    /// Since the size of the remaining records is unknown decoding stops at the first
+    /// such record, which is returned as `Raw::Undecoded` after the events decoded up
+    /// to that point.
+    pub fn set_lenient(&mut self, lenient: bool) {
+        self.lenient = lenient;
+    }
+
     /// Decode events.
-    pub fn decode_events(&self, input: &mut &[u8]) -> Result<Vec<(Phase, Raw)>, Error> {
+    pub fn decode_events(
+        &self,
+        input: &mut &[u8],
+    ) -> Result<Vec<(Phase, Raw<T>)>, Error> {
         let compact_len = <Compact<u32>>::decode(input)?;
-        let len = compact_len.0 as usize;
+        let len = compact_len.0;
 
         let mut r = Vec::new();
-        for _ in 0..len {
+        for index in 0..len {
             // decode EventRecord
             let phase = Phase::decode(input)?;
-            let module_variant = input.read_byte()?;
-
-            let module = self.metadata.module_with_events(module_variant)?;
-            let event_variant = input.read_byte()?;
-            let event_metadata = module.event(event_variant)?;
-
-            log::debug!(
-                "received event '{}::{}' ({:?})",
-                module.name(),
-                event_metadata.name,
-                event_metadata.arguments()
-            );
-
-            let mut event_data = Vec::<u8>::new();
+            let record = *input;
             let mut event_errors = Vec::<RuntimeError>::new();
-            let result = self.decode_raw_bytes(
-                &event_metadata.arguments(),
-                input,
-                &mut event_data,
-                &mut event_errors,
-            );
-            let raw = match result {
-                Ok(()) => {
-                    log::debug!("raw bytes: {}", hex::encode(&event_data),);
-
-                    let event = RawEvent {
-                        module: module.name().to_string(),
-                        variant: event_metadata.name.clone(),
-                        data: event_data,
-                    };
-
+            let result = self
+                .decode_event(input, &mut event_errors)
+                .and_then(|event| {
                     // topics come after the event data in EventRecord
-                    let _topics = Vec::<T::Hash>::decode(input)?;
-                    Raw::Event(event)
+                    let topics = Vec::<T::Hash>::decode(input)?;
+                    Ok((event, topics))
+                });
+            let raw = match result {
+                Ok((event, topics)) => {
+                    Raw::Event(EventRecord {
+                        index,
+                        phase: phase.clone(),
+                        event,
+                        topics,
+                    })
+                }
+                Err(err) if self.lenient => {
+                    let (module, variant) = self.event_names(record);
+                    log::warn!(
+                        "failed to decode event {} ({:?}::{:?}), skipping {} records: {}",
+                        index,
+                        module,
+                        variant,
+                        len - index - 1,
+                        err
+                    );
+                    r.push((
+                        phase,
+                        Raw::Undecoded(UndecodedEvents {
+                            index,
+                            module,
+                            variant,
+                            reason: err,
+                            remaining: len - index - 1,
+                        }),
+                    ));
+                    break
                 }
                 Err(err) => return Err(err),
             };
@@ -266,12 +736,207 @@ impl<T: System> EventsDecoder<T> {
         }
         Ok(r)
     }
+
+    fn decode_event(
+        &self,
+        input: &mut &[u8],
+        errors: &mut Vec<RuntimeError>,
+    ) -> Result<RawEvent, Error> {
+        let module_variant = input.read_byte()?;
+        let module = self.metadata.module_with_events(module_variant)?;
+        let event_variant = input.read_byte()?;
+        let event_metadata = module.event(event_variant)?;
+
+        log::debug!(
+            "received event '{}::{}' ({:?})",
+            module.name(),
+            event_metadata.name,
+            event_metadata.arguments()
+        );
+
+        let mut event_data = Vec::<u8>::new();
+        self.decode_raw_bytes(
+            &event_metadata.arguments(),
+            input,
+            &mut event_data,
+            errors,
+        )?;
+
+        log::debug!("raw bytes: {}", hex::encode(&event_data),);
+
+        Ok(RawEvent {
+            module: module.name().to_string(),
+            variant: event_metadata.name.clone(),
+            data: event_data,
+        })
+    }
+
+    /// Returns the module and event names of an encoded event, as far as they are known.
+    fn event_names(&self, record: &[u8]) -> (Option<String>, Option<String>) {
+        let module = match record
+            .get(0)
+            .and_then(|index| self.metadata.module_with_events(*index).ok())
+        {
+            Some(module) => module,
+            None => return (None, None),
+        };
+        let variant = record
+            .get(1)
+            .and_then(|index| module.event(*index).ok())
+            .map(|event| event.name.clone());
+        (Some(module.name().to_string()), variant)
+    }
+}
+
+/// Returns the variant of an enum type definition with the given index.
+fn enum_variant(
+    variants: &[(String, EventArg)],
+    index: u8,
+) -> Result<&(String, EventArg), Error> {
+    variants
+        .get(index as usize)
+        .ok_or_else(|| Error::Other(format!("unexpected enum variant index {}", index)))
 }
 
+/// Converts a dispatch error into a `Value`, e.g. a module error into the `Module`
+/// variant with the indices of the module and the error.
+fn dispatch_error_value(error: DispatchError) -> Value {
+    let (name, values) = match error {
+        DispatchError::Other(_) => ("Other", vec![]),
+        DispatchError::CannotLookup => ("CannotLookup", vec![]),
+        DispatchError::BadOrigin => ("BadOrigin", vec![]),
+        DispatchError::Module { index, error, .. } => {
+            (
+                "Module",
+                vec![Value::UInt(index.into()), Value::UInt(error.into())],
+            )
+        }
+    };
+    Value::Variant(name.into(), Box::new(Value::Composite(values)))
+}
+
+/// Returns the part of `start` which was consumed to arrive at `input`.
+fn consumed<'a>(start: &'a [u8], input: &[u8]) -> &'a [u8] {
+    &start[..start.len() - input.len()]
+}
+
+/// Raw event or error event.
 #[derive(Debug)]
-pub enum Raw {
-    Event(RawEvent),
+pub enum Raw<T: System> {
+    /// Event
+    Event(EventRecord<T>),
+    /// Error
     Error(RuntimeError),
+    /// Records which could not be decoded in lenient mode.
+    Undecoded(UndecodedEvents),
+}
+
+/// The events of a block, grouped by the phase they were emitted in.
+#[derive(Debug)]
+pub struct BlockEvents<T: System> {
+    /// Block hash.
+    pub block: T::Hash,
+    /// Events emitted while initializing the block.
+    pub initialization: Vec<EventRecord<T>>,
+    /// Events emitted by each extrinsic, by extrinsic index.
+    pub extrinsics: BTreeMap<u32, Vec<EventRecord<T>>>,
+    /// Events emitted while finalizing the block.
+    pub finalization: Vec<EventRecord<T>>,
+    /// Runtime errors of failed extrinsics.
+    pub errors: Vec<(Phase, RuntimeError)>,
+    /// Records which could not be decoded in lenient mode.
+    pub undecoded: Option<UndecodedEvents>,
+}
+
+impl<T: System> BlockEvents<T> {
+    /// Groups the decoded events of a block.
+    pub fn new(block: T::Hash, events: Vec<(Phase, Raw<T>)>) -> Self {
+        let mut block_events = Self {
+            block,
+            initialization: Vec::new(),
+            extrinsics: BTreeMap::new(),
+            finalization: Vec::new(),
+            errors: Vec::new(),
+            undecoded: None,
+        };
+        for (phase, raw) in events {
+            match raw {
+                Raw::Event(record) => {
+                    match phase {
+                        Phase::Initialization => block_events.initialization.push(record),
+                        Phase::ApplyExtrinsic(index) => {
+                            block_events
+                                .extrinsics
+                                .entry(index)
+                                .or_default()
+                                .push(record)
+                        }
+                        Phase::Finalization => block_events.finalization.push(record),
+                    }
+                }
+                Raw::Error(err) => block_events.errors.push((phase, err)),
+                Raw::Undecoded(undecoded) => block_events.undecoded = Some(undecoded),
+            }
+        }
+        block_events
+    }
+
+    /// Returns the events emitted by the extrinsic with the given index.
+    pub fn extrinsic(&self, index: u32) -> &[EventRecord<T>] {
+        self.extrinsics
+            .get(&index)
+            .map(|events| &events[..])
+            .unwrap_or(&[])
+    }
+
+    /// Returns all events in the order they were emitted.
+    pub fn iter(&self) -> impl Iterator<Item = &EventRecord<T>> {
+        self.initialization
+            .iter()
+            .chain(self.extrinsics.values().flatten())
+            .chain(self.finalization.iter())
+    }
+
+    /// Finds all events of the given type, attempting to decode the event data.
+    /// Returns `Err` if the data of an event fails to decode into the supplied type.
+    pub fn find_events<E: Event<T>>(&self) -> Result<Vec<(Phase, E)>, codec::Error> {
+        self.iter()
+            .filter(|record| {
+                record.event.module == E::MODULE && record.event.variant == E::EVENT
+            })
+            .map(|record| {
+                Ok((
+                    record.phase.clone(),
+                    E::decode(&mut &record.event.data[..])?,
+                ))
+            })
+            .collect()
+    }
+}
+
+/// The event records of a block which could not be decoded.
+#[derive(Debug)]
+pub struct UndecodedEvents {
+    /// The index of the first record which could not be decoded.
+    pub index: u32,
+    /// The module which emitted the event, if known.
+    pub module: Option<String>,
+    /// The name of the event, if known.
+    pub variant: Option<String>,
+    /// The reason the event could not be decoded.
+    pub reason: Error,
+    /// The number of records following the undecoded one, which were skipped.
+    pub remaining: u32,
+}
+
+impl fmt::Display for UndecodedEvents {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "failed to decode event {} ({:?}::{:?}), skipped {} records: {}",
+            self.index, self.module, self.variant, self.remaining, self.reason
+        )
+    }
 }
 
 #[cfg(test)]
@@ -315,6 +980,225 @@ mod tests {
         assert_eq!(output, vec![1, 0]);
     }
 
+    #[test]
+    fn test_decode_dispatch_result() {
+        let decoder = EventsDecoder::<TestRuntime>::new(Metadata::default());
+
+        let value: (DispatchResult, u8) = (Ok(()), 1);
+        let input = value.encode();
+        let mut output = Vec::<u8>::new();
+        let mut errors = Vec::<RuntimeError>::new();
+
+        decoder
+            .decode_raw_bytes(
+                &[
+                    EventArg::Primitive("DispatchResult".to_string()),
+                    EventArg::Primitive("u8".to_string()),
+                ],
+                &mut &input[..],
+                &mut output,
+                &mut errors,
+            )
+            .unwrap();
+
+        // the result is not part of the event data
+        assert_eq!(output, vec![1]);
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn test_decode_nested_dispatch_error() {
+        let decoder = EventsDecoder::<TestRuntime>::new(Metadata::default());
+
+        let error = DispatchError::Module {
+            index: 1,
+            error: 2,
+            message: None,
+        };
+        let input = (Some(error), 3u8).encode();
+        let args: Vec<EventArg> = vec![
+            "Option<DispatchError>".parse().unwrap(),
+            "u8".parse().unwrap(),
+        ];
+        let mut output = Vec::<u8>::new();
+        let mut errors = Vec::<RuntimeError>::new();
+
+        decoder
+            .decode_raw_bytes(&args, &mut &input[..], &mut output, &mut errors)
+            .unwrap();
+
+        // a nested error is part of the event data instead of a runtime error
+        assert_eq!(output, input);
+        assert!(errors.is_empty());
+
+        let value = decoder
+            .decode_arg_value(&args[0], &mut &output[..])
+            .unwrap();
+        assert_eq!(
+            value,
+            Value::Option(Some(Box::new(Value::Variant(
+                "Module".into(),
+                Box::new(Value::Composite(vec![Value::UInt(1), Value::UInt(2)])),
+            ))))
+        );
+    }
+
+    #[test]
+    fn test_decode_compact() {
+        let decoder = EventsDecoder::<TestRuntime>::new(Metadata::default());
+
+        let value = (Compact(u64::MAX), 1u8);
+        let input = value.encode();
+        let mut output = Vec::<u8>::new();
+        let mut errors = Vec::<RuntimeError>::new();
+
+        decoder
+            .decode_raw_bytes(
+                &[
+                    "Compact<Balance>".parse().unwrap(),
+                    EventArg::Primitive("u8".to_string()),
+                ],
+                &mut &input[..],
+                &mut output,
+                &mut errors,
+            )
+            .unwrap();
+
+        assert_eq!(output, input);
+    }
+
+    #[test]
+    fn test_decode_registered_type_decoder() {
+        let mut decoder = EventsDecoder::<TestRuntime>::new(Metadata::default());
+        decoder.register_type_decoder::<Vec<(u32, u64)>>("Schedule");
+
+        let value = vec![(1u32, 2u64), (3u32, 4u64)];
+        let input = value.encode();
+        let mut output = Vec::<u8>::new();
+        let mut errors = Vec::<RuntimeError>::new();
+
+        decoder
+            .decode_raw_bytes(
+                &[EventArg::Primitive("Schedule".to_string())],
+                &mut &input[..],
+                &mut output,
+                &mut errors,
+            )
+            .unwrap();
+
+        assert_eq!(output, input);
+    }
+
+    #[test]
+    fn test_decode_nested_args() {
+        let decoder = EventsDecoder::<TestRuntime>::new(Metadata::default());
+
+        let mut map = std::collections::BTreeMap::new();
+        map.insert(1u32, vec![([1u8; 4], Compact(7u64))]);
+        let result: Result<(), u8> = Err(3);
+        let input = (map, result).encode();
+        let mut output = Vec::<u8>::new();
+        let mut errors = Vec::<RuntimeError>::new();
+
+        decoder
+            .decode_raw_bytes(
+                &[
+                    "BTreeMap<u32, Vec<([u8; 4], Compact<u64>)>>"
+                        .parse()
+                        .unwrap(),
+                    "Result<(), u8>".parse().unwrap(),
+                ],
+                &mut &input[..],
+                &mut output,
+                &mut errors,
+            )
+            .unwrap();
+
+        assert_eq!(output, input);
+    }
+
+    #[test]
+    fn test_decode_arg_value() {
+        let decoder = EventsDecoder::<TestRuntime>::new(Metadata::default());
+
+        let account = [7u8; 32];
+        let result: Result<(), u8> = Err(3);
+        let input = (
+            (account, b"subxt".to_vec()),
+            Compact(10_000u128),
+            Some(5u32),
+            result,
+        )
+            .encode();
+        let args: Vec<EventArg> = vec![
+            "(AccountId, Vec<u8>)".parse().unwrap(),
+            "Compact<Balance>".parse().unwrap(),
+            "Option<u32>".parse().unwrap(),
+            "Result<(), u8>".parse().unwrap(),
+        ];
+
+        let input = &mut &input[..];
+        let values = args
+            .iter()
+            .map(|arg| decoder.decode_arg_value(arg, input).unwrap())
+            .collect::<Vec<_>>();
+
+        assert!(input.is_empty());
+        assert_eq!(
+            values,
+            vec![
+                Value::Composite(vec![
+                    Value::AccountId(account.to_vec()),
+                    Value::Bytes(b"subxt".to_vec()),
+                ]),
+                Value::UInt(10_000),
+                Value::Option(Some(Box::new(Value::UInt(5)))),
+                Value::Variant("Err".into(), Box::new(Value::UInt(3))),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decode_type_registry() {
+        let mut decoder = EventsDecoder::<TestRuntime>::new(Metadata::default());
+        let registry = TypeRegistry::from_json(
+            r#"{
+                "types": {
+                    "Kitty": { "name": "Vec<u8>", "price": "Option<u128>" },
+                    "Offer": { "_enum": { "None": "Null", "Fixed": "Compact<Balance>" } }
+                }
+            }"#,
+        )
+        .unwrap();
+        decoder.register_type_registry(&registry, 0);
+
+        let input = ((b"tom".to_vec(), Some(5u128)), 1u8, Compact(7u128)).encode();
+        let args: Vec<EventArg> =
+            vec!["Kitty".parse().unwrap(), "Offer".parse().unwrap()];
+
+        let mut output = Vec::<u8>::new();
+        decoder
+            .decode_raw_bytes(&args, &mut &input[..], &mut output, &mut vec![])
+            .unwrap();
+        assert_eq!(output, input);
+
+        let input = &mut &input[..];
+        let values = args
+            .iter()
+            .map(|arg| decoder.decode_arg_value(arg, input).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            values,
+            vec![
+                Value::Composite(vec![
+                    Value::Bytes(b"tom".to_vec()),
+                    Value::Option(Some(Box::new(Value::UInt(5)))),
+                ]),
+                Value::Variant("Fixed".into(), Box::new(Value::UInt(7))),
+            ]
+        );
+    }
+
     #[test]
     fn test_decode_system_events_and_error() {
         let decoder = EventsDecoder::<TestRuntime>::new(
@@ -386,6 +1270,78 @@ mod tests {
 
         // [(ApplyExtrinsic(0), Event(RawEvent { module: "System", variant: "ExtrinsicSuccess", data: "482d7c09000000000200" })), (ApplyExtrinsic(1), Error(Module(ModuleError { module: "System", error: "NonDefaultComposite" }))), (ApplyExtrinsic(2), Error(Module(ModuleError { module: "System", error: "NonDefaultComposite" })))]
         let input = hex::decode("0c00000000000000482d7c0900000000020000000100000000010300035884723300000000000000000200000000010300035884723300000000000000").unwrap();
-        decoder.decode_events(&mut &input[..]).unwrap();
+        let events = decoder.decode_events(&mut &input[..]).unwrap();
+        assert_eq!(events.len(), 3);
+        if let (Phase::ApplyExtrinsic(0), Raw::Event(record)) = &events[0] {
+            assert_eq!(record.index, 0);
+            assert_eq!(record.event.variant, "ExtrinsicSuccess");
+            assert!(record.topics.is_empty());
+        } else {
+            panic!("expected an event, got {:?}", events[0]);
+        }
+    }
+
+    #[test]
+    fn test_decode_events_lenient() {
+        let event = |name: &str, args: Vec<String>| {
+            EventMetadata {
+                name: DecodeDifferent::Decoded(name.to_string()),
+                arguments: DecodeDifferent::Decoded(args),
+                documentation: DecodeDifferent::Decoded(vec![]),
+            }
+        };
+        let mut decoder = EventsDecoder::<TestRuntime>::new(
+            Metadata::try_from(RuntimeMetadataPrefixed(
+                META_RESERVED,
+                RuntimeMetadata::V12(RuntimeMetadataV12 {
+                    modules: DecodeDifferent::Decoded(vec![ModuleMetadata {
+                        name: DecodeDifferent::Decoded("System".to_string()),
+                        storage: None,
+                        calls: None,
+                        event: Some(DecodeDifferent::Decoded(vec![
+                            event("ExtrinsicSuccess", vec!["DispatchInfo".to_string()]),
+                            event("Mystery", vec!["Unknown".to_string()]),
+                        ])),
+                        constants: DecodeDifferent::Decoded(vec![]),
+                        errors: DecodeDifferent::Decoded(vec![]),
+                        index: 0,
+                    }]),
+                    extrinsic: ExtrinsicMetadata {
+                        version: 0,
+                        signed_extensions: vec![],
+                    },
+                }),
+            ))
+            .unwrap(),
+        );
+
+        let mut input = Compact(3u32).encode();
+        for (phase, variant) in &[(0u32, 0u8), (1, 1), (2, 0)] {
+            // Phase::ApplyExtrinsic
+            input.push(0);
+            input.extend(phase.encode());
+            input.extend(&[0, *variant]);
+            input.extend(DispatchInfo::default().encode());
+            input.extend(Vec::<u8>::new().encode());
+        }
+
+        assert!(decoder.decode_events(&mut &input[..]).is_err());
+
+        decoder.set_lenient(true);
+        let events = decoder.decode_events(&mut &input[..]).unwrap();
+        assert_eq!(events.len(), 2);
+        if let (Phase::ApplyExtrinsic(0), Raw::Event(record)) = &events[0] {
+            assert_eq!(record.event.variant, "ExtrinsicSuccess");
+        } else {
+            panic!("expected an event, got {:?}", events[0]);
+        }
+        if let (Phase::ApplyExtrinsic(1), Raw::Undecoded(undecoded)) = &events[1] {
+            assert_eq!(undecoded.index, 1);
+            assert_eq!(undecoded.module.as_deref(), Some("System"));
+            assert_eq!(undecoded.variant.as_deref(), Some("Mystery"));
+            assert_eq!(undecoded.remaining, 1);
+        } else {
+            panic!("expected undecoded events, got {:?}", events[1]);
+        }
     }
 }
@@ -24,11 +24,17 @@ use core::{
 };
 use sp_runtime::{
     generic::Era,
-    traits::SignedExtension,
+    traits::{
+        SignedExtension,
+        UniqueSaturatedFrom,
+        UniqueSaturatedInto,
+    },
     transaction_validity::TransactionValidityError,
 };
+use sp_version::RuntimeVersion;
 
 use crate::{
+    error::Error,
     frame::{
         balances::Balances,
         system::System,
@@ -138,14 +144,14 @@ where
 ///
 /// # Note
 ///
-/// This is modified from the substrate version to allow passing in of the genesis hash, which is
-/// returned via `additional_signed()`. It assumes therefore `Era::Immortal` (The transaction is
-/// valid forever)
+/// This is modified from the substrate version to allow passing in of the hash of the block
+/// the era starts at, which is returned via `additional_signed()`. For `Era::Immortal` this is
+/// the genesis hash.
 #[derive(Encode, Decode, Clone, Eq, PartialEq, Debug)]
 pub struct CheckEra<T: System>(
     /// The default structure for the Extra encoding
     pub (Era, PhantomData<T>),
-    /// Local genesis hash to be used for `AdditionalSigned`
+    /// Local hash of the block the era starts at to be used for `AdditionalSigned`
     #[codec(skip)]
     pub T::Hash,
 );
@@ -227,18 +233,122 @@ where
     }
 }
 
+/// Mortality of a transaction.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Mortality {
+    /// The transaction is valid forever.
+    ///
+    /// An immortal transaction can be replayed if the account is reaped and its nonce
+    /// starts over.
+    Immortal,
+    /// The transaction is valid for a number of blocks after the block it was signed
+    /// at.
+    ///
+    /// The period is rounded to a power of two between 4 and 65536.
+    Mortal(u64),
+}
+
+impl Default for Mortality {
+    fn default() -> Self {
+        Mortality::Mortal(64)
+    }
+}
+
+/// The chain state a transaction is signed against, from which a `SignedExtra` is
+/// created.
+#[derive(Clone, Debug)]
+pub struct ExtraContext<T: System> {
+    /// Spec version of the runtime.
+    pub spec_version: u32,
+    /// Transaction version of the runtime.
+    pub tx_version: u32,
+    /// Nonce of the signer.
+    pub nonce: T::Index,
+    /// Genesis hash of the chain.
+    pub genesis_hash: T::Hash,
+    /// Era of the transaction.
+    pub era: Era,
+    /// Hash of the block the era starts at, the genesis hash for immortal transactions.
+    pub era_hash: T::Hash,
+    /// Tip for the block author, signing fails if it does not fit the balance type of
+    /// the runtime.
+    pub tip: u128,
+}
+
+impl<T: System> ExtraContext<T> {
+    /// Creates the context of an immortal transaction.
+    pub fn new(
+        runtime_version: &RuntimeVersion,
+        genesis_hash: T::Hash,
+        nonce: T::Index,
+    ) -> Self {
+        Self {
+            spec_version: runtime_version.spec_version,
+            tx_version: runtime_version.transaction_version,
+            nonce,
+            genesis_hash,
+            era: Era::Immortal,
+            era_hash: genesis_hash,
+            tip: 0,
+        }
+    }
+
+    /// Sets the era of the transaction and the hash of the block it starts at.
+    pub fn set_era(&mut self, era: Era, era_hash: T::Hash) {
+        self.era = era;
+        self.era_hash = era_hash;
+    }
+}
+
+/// Options for signing a transaction.
+#[derive(Clone, Debug)]
+pub struct SignOptions<T: System> {
+    /// Nonce of the transaction, the nonce of the signer or of the account if not set.
+    pub nonce: Option<T::Index>,
+    /// Tip for the block author, increasing the priority of the transaction.
+    pub tip: u128,
+    /// Mortality of the transaction, the mortality of the client if not set.
+    pub mortality: Option<Mortality>,
+    /// Number and hash of the block a mortal transaction is valid from, the finalized
+    /// head if not set. An offline client can not fetch the finalized head, so it has
+    /// to sign mortal transactions with a checkpoint, or sign immortal transactions.
+    pub checkpoint: Option<(T::BlockNumber, T::Hash)>,
+}
+
+impl<T: System> Default for SignOptions<T> {
+    fn default() -> Self {
+        Self {
+            nonce: None,
+            tip: 0,
+            mortality: None,
+            checkpoint: None,
+        }
+    }
+}
+
+impl<T: System> SignOptions<T> {
+    /// Raises the tip by a percentage, and at least by one.
+    pub fn raise_tip(&mut self, percent: u32) {
+        let raise = self.tip.saturating_mul(percent as u128) / 100;
+        self.tip = self.tip.saturating_add(raise.max(1));
+    }
+}
+
 /// Trait for implementing transaction extras for a runtime.
 pub trait SignedExtra<T: System>: SignedExtension {
     /// The type the extras.
-    type Extra: SignedExtension + Send + Sync;
+    type Extra: SignedExtension<AdditionalSigned = Self::Additional> + Send + Sync;
+    /// The data signed in addition to the extras, e.g. the genesis hash.
+    type Additional: Encode + Send + Sync;
+
+    /// Checks that the extras can be created from a context, e.g. that its tip fits
+    /// the balance type of the runtime.
+    fn check_context(_context: &ExtraContext<T>) -> Result<(), Error> {
+        Ok(())
+    }
 
     /// Creates a new `SignedExtra`.
-    fn new(
-        spec_version: u32,
-        tx_version: u32,
-        nonce: T::Index,
-        genesis_hash: T::Hash,
-    ) -> Self;
+    fn new(context: ExtraContext<T>) -> Self;
 
     /// Returns the transaction extra.
     fn extra(&self) -> Self::Extra;
@@ -251,6 +361,9 @@ pub struct DefaultExtra<T: System> {
     tx_version: u32,
     nonce: T::Index,
     genesis_hash: T::Hash,
+    era: Era,
+    era_hash: T::Hash,
+    tip: u128,
 }
 
 impl<T: System + Balances + Clone + Debug + Eq + Send + Sync> SignedExtra<T>
@@ -265,18 +378,27 @@ impl<T: System + Balances + Clone + Debug + Eq + Send + Sync> SignedExtra<T>
         CheckWeight<T>,
         ChargeTransactionPayment<T>,
     );
+    type Additional =
+        <<Self as SignedExtra<T>>::Extra as SignedExtension>::AdditionalSigned;
 
-    fn new(
-        spec_version: u32,
-        tx_version: u32,
-        nonce: T::Index,
-        genesis_hash: T::Hash,
-    ) -> Self {
+    fn check_context(context: &ExtraContext<T>) -> Result<(), Error> {
+        let tip = <T as Balances>::Balance::unique_saturated_from(context.tip);
+        if UniqueSaturatedInto::<u128>::unique_saturated_into(tip) == context.tip {
+            Ok(())
+        } else {
+            Err(Error::TipOverflow(context.tip))
+        }
+    }
+
+    fn new(context: ExtraContext<T>) -> Self {
         DefaultExtra {
-            spec_version,
-            tx_version,
-            nonce,
-            genesis_hash,
+            spec_version: context.spec_version,
+            tx_version: context.tx_version,
+            nonce: context.nonce,
+            genesis_hash: context.genesis_hash,
+            era: context.era,
+            era_hash: context.era_hash,
+            tip: context.tip,
         }
     }
 
@@ -285,10 +407,12 @@ impl<T: System + Balances + Clone + Debug + Eq + Send + Sync> SignedExtra<T>
             CheckSpecVersion(PhantomData, self.spec_version),
             CheckTxVersion(PhantomData, self.tx_version),
             CheckGenesis(PhantomData, self.genesis_hash),
-            CheckEra((Era::Immortal, PhantomData), self.genesis_hash),
+            CheckEra((self.era, PhantomData), self.era_hash),
             CheckNonce(self.nonce),
             CheckWeight(PhantomData),
-            ChargeTransactionPayment(<T as Balances>::Balance::default()),
+            ChargeTransactionPayment(<T as Balances>::Balance::unique_saturated_from(
+                self.tip,
+            )),
         )
     }
 }
@@ -299,8 +423,7 @@ impl<T: System + Balances + Clone + Debug + Eq + Send + Sync> SignedExtension
     const IDENTIFIER: &'static str = "DefaultExtra";
     type AccountId = T::AccountId;
     type Call = ();
-    type AdditionalSigned =
-        <<Self as SignedExtra<T>>::Extra as SignedExtension>::AdditionalSigned;
+    type AdditionalSigned = <Self as SignedExtra<T>>::Additional;
     type Pre = ();
 
     fn additional_signed(
@@ -309,3 +432,26 @@ impl<T: System + Balances + Clone + Debug + Eq + Send + Sync> SignedExtension
         self.extra().additional_signed()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DefaultNodeRuntime;
+
+    #[test]
+    fn test_raise_tip() {
+        let mut options = SignOptions::<DefaultNodeRuntime> {
+            nonce: Some(3),
+            ..Default::default()
+        };
+        options.raise_tip(10);
+        assert_eq!(options.tip, 1);
+        options.tip = 1_000;
+        options.raise_tip(10);
+        assert_eq!(options.tip, 1_100);
+        options.tip = u128::MAX;
+        options.raise_tip(10);
+        assert_eq!(options.tip, u128::MAX);
+        assert_eq!(options.nonce, Some(3));
+    }
+}
@@ -23,6 +23,9 @@ pub use self::{
     extra::{
         DefaultExtra,
         Extra,
+        ExtraContext,
+        Mortality,
+        SignOptions,
         SignedExtra,
     },
     signer::{
@@ -31,9 +34,6 @@ pub use self::{
     },
 };
 
-use sp_runtime::traits::SignedExtension;
-use sp_version::RuntimeVersion;
-
 use crate::{
     frame::system::System,
     runtimes::Runtime,
@@ -54,20 +54,15 @@ pub type SignedPayload<T> = sp_runtime::generic::SignedPayload<Encoded, Extra<T>
 
 /// Creates a signed extrinsic
 pub async fn create_signed<T>(
-    runtime_version: &RuntimeVersion,
-    genesis_hash: T::Hash,
-    nonce: T::Index,
+    context: ExtraContext<T>,
     call: Encoded,
     signer: &(dyn Signer<T> + Send + Sync),
 ) -> Result<UncheckedExtrinsic<T>, Error>
 where
     T: Runtime,
-    <<T::Extra as SignedExtra<T>>::Extra as SignedExtension>::AdditionalSigned:
-        Send + Sync,
 {
-    let spec_version = runtime_version.spec_version;
-    let tx_version = runtime_version.transaction_version;
-    let extra: T::Extra = T::Extra::new(spec_version, tx_version, nonce, genesis_hash);
+    T::Extra::check_context(&context)?;
+    let extra: T::Extra = T::Extra::new(context);
     let payload = SignedPayload::<T>::new(call, extra.extra())?;
     let signed = signer.sign(payload).await?;
     Ok(signed)
@@ -18,7 +18,6 @@
 //! [substrate](https://github.com/paritytech/substrate) node via RPC.
 
 use super::{
-    SignedExtra,
     SignedPayload,
     UncheckedExtrinsic,
 };
@@ -27,7 +26,6 @@ use codec::Encode;
 use sp_core::Pair;
 use sp_runtime::traits::{
     IdentifyAccount,
-    SignedExtension,
     Verify,
 };
 use std::{
@@ -100,7 +98,6 @@ impl<T, P> Signer<T> for PairSigner<T, P>
 where
     T: Runtime,
     T::AccountId: Into<T::Address> + 'static,
-    <<T::Extra as SignedExtra<T>>::Extra as SignedExtension>::AdditionalSigned: Send,
     P: Pair + 'static,
     P::Signature: Into<T::Signature> + 'static,
 {
@@ -18,6 +18,7 @@
 
 use crate::frame::system::{
     System,
+    SystemCallEncoder,
     SystemEventsDecoder,
 };
 use codec::{
@@ -72,6 +73,14 @@ pub struct AccountData<Balance> {
     pub fee_frozen: Balance,
 }
 
+/// The minimum amount required to keep an account open.
+#[derive(Clone, Debug, Eq, PartialEq, Constant)]
+pub struct ExistentialDepositConstant<T: Balances> {
+    #[constant(returns = T::Balance)]
+    /// Runtime marker.
+    pub _runtime: PhantomData<T>,
+}
+
 /// The total issuance of the balances module.
 #[derive(Clone, Debug, Eq, PartialEq, Store, Encode)]
 pub struct TotalIssuanceStore<T: Balances> {
@@ -120,7 +129,6 @@ mod tests {
             PairSigner,
             Signer,
         },
-        subscription::EventSubscription,
         system::AccountStoreExt,
         tests::{
             test_client,
@@ -181,6 +189,27 @@ mod tests {
         assert_ne!(info.data.free, 0);
     }
 
+    #[async_std::test]
+    async fn test_existential_deposit_constant() {
+        env_logger::try_init().ok();
+        let (client, _) = test_client().await;
+        let existential_deposit = client.existential_deposit().unwrap();
+        assert_ne!(existential_deposit, 0);
+    }
+
+    #[async_std::test]
+    async fn test_check_compatibility() {
+        env_logger::try_init().ok();
+        let (client, _) = test_client().await;
+        client
+            .check_compatibility::<(
+                TransferCall<'static, TestRuntime>,
+                TransferEvent<TestRuntime>,
+                TotalIssuanceStore<TestRuntime>,
+            )>()
+            .unwrap();
+    }
+
     #[async_std::test]
     async fn test_transfer_error() {
         env_logger::try_init().ok();
@@ -211,14 +240,14 @@ mod tests {
         let alice = PairSigner::new(AccountKeyring::Alice.pair());
         let bob = AccountKeyring::Bob.to_account_id();
         let (client, _) = test_client().await;
-        let sub = client.subscribe_events().await.unwrap();
-        let mut decoder = EventsDecoder::<TestRuntime>::new(client.metadata().clone());
+        let mut decoder = EventsDecoder::<TestRuntime>::new((*client.metadata()).clone());
         decoder.with_balances();
-        let mut sub = EventSubscription::<TestRuntime>::new(sub, decoder);
+        let mut sub = client.event_subscription(decoder).await.unwrap();
         sub.filter_event::<TransferEvent<_>>();
         client.transfer(&alice, &bob, 10_000).await.unwrap();
-        let raw = sub.next().await.unwrap().unwrap();
-        let event = TransferEvent::<TestRuntime>::decode(&mut &raw.data[..]).unwrap();
+        let record = sub.next().await.unwrap().unwrap();
+        let event =
+            TransferEvent::<TestRuntime>::decode(&mut &record.event.data[..]).unwrap();
         assert_eq!(
             event,
             TransferEvent {
@@ -228,4 +257,33 @@ mod tests {
             }
         );
     }
+
+    #[async_std::test]
+    async fn test_events_at() {
+        env_logger::try_init().ok();
+        let alice = PairSigner::new(AccountKeyring::Alice.pair());
+        let bob = AccountKeyring::Bob.to_account_id();
+        let (client, _) = test_client().await;
+        let success = client
+            .transfer_and_watch(&alice, &bob, 10_000)
+            .await
+            .unwrap();
+
+        let mut decoder = EventsDecoder::<TestRuntime>::new((*client.metadata()).clone());
+        decoder.with_balances();
+        let events = client.events_at(success.block, &decoder).await.unwrap();
+        let transfers = events.find_events::<TransferEvent<_>>().unwrap();
+        assert_eq!(transfers.len(), 1);
+        assert_eq!(transfers[0].1.amount, 10_000);
+
+        let number = client
+            .header(Some(success.block))
+            .await
+            .unwrap()
+            .unwrap()
+            .number;
+        let blocks = client.events_range(0..=number, &decoder).await.unwrap();
+        assert_eq!(blocks.len() as u32, number + 1);
+        assert_eq!(blocks.last().unwrap().block, success.block);
+    }
 }
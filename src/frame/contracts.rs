@@ -19,10 +19,12 @@
 use crate::frame::{
     balances::{
         Balances,
+        BalancesCallEncoder,
         BalancesEventsDecoder,
     },
     system::{
         System,
+        SystemCallEncoder,
         SystemEventsDecoder,
     },
 };
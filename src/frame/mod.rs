@@ -19,6 +19,7 @@
 use crate::{
     events::EventsDecoder,
     metadata::{
+        Incompatibility,
         Metadata,
         MetadataError,
     },
@@ -44,6 +45,12 @@ pub trait Store<T>: Encode {
     const FIELD: &'static str;
     /// Return type.
     type Returns: Decode;
+    /// Type names of the keys, which `check_store` compares with the metadata if they
+    /// are set. Set by the derive.
+    const KEY_TYPES: Option<&'static [&'static str]> = None;
+    /// Type name of the value, which `check_store` compares with the metadata if it is
+    /// set. Set by the derive.
+    const RETURN_TYPE: Option<&'static str> = None;
     /// Returns the key prefix for storage maps
     fn prefix(metadata: &Metadata) -> Result<StorageKey, MetadataError>;
     /// Returns the `StorageKey`.
@@ -57,20 +64,105 @@ pub trait Store<T>: Encode {
     }
 }
 
+/// Decodes the map keys of a storage entry. Implemented by the `Store` derive.
+pub trait StoreKey<T>: Store<T> {
+    /// Type of the map keys, `()` for plain storage entries and a tuple for double maps.
+    type Key: Decode;
+    /// Decodes the map keys from a `StorageKey`.
+    ///
+    /// Fails for keys hashed with a hasher which does not retain the key.
+    fn decode_key(
+        metadata: &Metadata,
+        key: &StorageKey,
+    ) -> Result<Self::Key, MetadataError>;
+}
+
 /// Call trait.
 pub trait Call<T>: Encode {
     /// Module name.
     const MODULE: &'static str;
     /// Function name.
     const FUNCTION: &'static str;
+    /// Type names of the arguments, which `check_call` compares with the metadata if
+    /// they are set. Set by the derive.
+    const ARG_TYPES: Option<&'static [&'static str]> = None;
     /// Load event decoder.
     fn events_decoder(_decoder: &mut EventsDecoder<T>) {}
 }
 
+/// Constant trait.
+pub trait Constant<T> {
+    /// Module name.
+    const MODULE: &'static str;
+    /// Constant name.
+    const CONSTANT: &'static str;
+    /// Return type.
+    type Returns: Decode;
+    /// Returns the decoded value of the constant.
+    fn value(metadata: &Metadata) -> Result<Self::Returns, MetadataError> {
+        metadata
+            .module(Self::MODULE)?
+            .constant(Self::CONSTANT)?
+            .value_as()
+    }
+}
+
 /// Event trait.
 pub trait Event<T>: Decode {
     /// Module name.
     const MODULE: &'static str;
     /// Event name.
     const EVENT: &'static str;
+    /// Type names of the arguments, which `check_event` compares with the metadata if
+    /// they are set. Set by the derive.
+    const ARG_TYPES: Option<&'static [&'static str]> = None;
 }
+
+/// Checks compiled types against the metadata of a runtime.
+///
+/// Implemented by the `Call`, `Store` and `Event` derives and for tuples of such types.
+/// Hand-written implementations can use `check_call`, `check_store` or `check_event`.
+pub trait CheckCompatibility<T> {
+    /// Returns every mismatch between the type and the metadata.
+    fn check_compatibility(metadata: &Metadata) -> Vec<Incompatibility>;
+}
+
+/// Compares a call with the metadata, and its argument types if they are set.
+pub fn check_call<T, C: Call<T>>(metadata: &Metadata) -> Vec<Incompatibility> {
+    metadata.check_call(C::MODULE, C::FUNCTION, C::ARG_TYPES)
+}
+
+/// Compares a storage entry with the metadata, and its key and value types if they
+/// are set.
+pub fn check_store<T, S: Store<T>>(metadata: &Metadata) -> Vec<Incompatibility> {
+    metadata.check_store(S::MODULE, S::FIELD, S::KEY_TYPES, S::RETURN_TYPE)
+}
+
+/// Compares an event with the metadata, and its argument types if they are set.
+pub fn check_event<T, E: Event<T>>(metadata: &Metadata) -> Vec<Incompatibility> {
+    metadata.check_event(E::MODULE, E::EVENT, E::ARG_TYPES)
+}
+
+macro_rules! impl_check_compatibility {
+    ($($ty:ident),+) => {
+        impl<T, $($ty: CheckCompatibility<T>),+> CheckCompatibility<T> for ($($ty,)+) {
+            fn check_compatibility(metadata: &Metadata) -> Vec<Incompatibility> {
+                let mut incompatibilities = Vec::new();
+                $(
+                    incompatibilities
+                        .extend(<$ty as CheckCompatibility<T>>::check_compatibility(metadata));
+                )+
+                incompatibilities
+            }
+        }
+    };
+}
+
+impl_check_compatibility!(A);
+impl_check_compatibility!(A, B);
+impl_check_compatibility!(A, B, C);
+impl_check_compatibility!(A, B, C, D);
+impl_check_compatibility!(A, B, C, D, E);
+impl_check_compatibility!(A, B, C, D, E, F);
+impl_check_compatibility!(A, B, C, D, E, F, G);
+impl_check_compatibility!(A, B, C, D, E, F, G, H);
@@ -17,6 +17,7 @@
 //! Session support
 use crate::frame::system::{
     System,
+    SystemCallEncoder as _,
     SystemEventsDecoder as _,
 };
 use codec::Encode;
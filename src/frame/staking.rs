@@ -18,6 +18,7 @@
 
 use super::balances::{
     Balances,
+    BalancesCallEncoder as _,
     BalancesEventsDecoder as _,
 };
 use codec::{
@@ -66,6 +67,22 @@ pub struct SetPayeeCall<T: Staking> {
 #[module]
 pub trait Staking: Balances {}
 
+/// Number of sessions per era.
+#[derive(Clone, Debug, Constant)]
+pub struct SessionsPerEraConstant<T: Staking> {
+    #[constant(returns = u32)]
+    /// Marker for the runtime
+    pub _runtime: PhantomData<T>,
+}
+
+/// Number of eras that staked funds must remain bonded for.
+#[derive(Clone, Debug, Constant)]
+pub struct BondingDurationConstant<T: Staking> {
+    #[constant(returns = EraIndex)]
+    /// Marker for the runtime
+    pub _runtime: PhantomData<T>,
+}
+
 /// Number of eras to keep in history.
 ///
 /// Information is kept for eras in `[current_era - history_depth; current_era]`.
@@ -19,6 +19,7 @@
 use crate::{
     frame::system::{
         System,
+        SystemCallEncoder,
         SystemEventsDecoder,
     },
     Encoded,
@@ -23,7 +23,10 @@ use codec::{
 };
 use core::marker::PhantomData;
 use frame_support::{
-    weights::DispatchInfo,
+    weights::{
+        DispatchInfo,
+        Weight,
+    },
     Parameter,
 };
 use serde::de::DeserializeOwned;
@@ -141,6 +144,22 @@ pub struct AccountStore<'a, T: System> {
     pub account_id: &'a T::AccountId,
 }
 
+/// Maximum number of block number to block hash mappings to keep.
+#[derive(Clone, Debug, Eq, PartialEq, Constant)]
+pub struct BlockHashCountConstant<T: System> {
+    #[constant(returns = T::BlockNumber)]
+    /// Runtime marker.
+    pub _runtime: PhantomData<T>,
+}
+
+/// The maximum weight of a block.
+#[derive(Clone, Debug, Eq, PartialEq, Constant)]
+pub struct MaximumBlockWeightConstant<T: System> {
+    #[constant(returns = Weight)]
+    /// Runtime marker.
+    pub _runtime: PhantomData<T>,
+}
+
 /// Arguments for updating the runtime code
 #[derive(Clone, Debug, Eq, PartialEq, Call, Encode)]
 pub struct SetCodeCall<'a, T: System> {
@@ -166,6 +185,8 @@ pub enum Phase {
     ApplyExtrinsic(u32),
     /// The end.
     Finalization,
+    /// Initializing the block.
+    Initialization,
 }
 
 /// An extrinsic completed successfully.
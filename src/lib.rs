@@ -50,7 +50,14 @@ pub use sp_core;
 pub use sp_runtime;
 
 use codec::Decode;
-use futures::future;
+use futures::{
+    future,
+    stream::{
+        self,
+        StreamExt,
+        TryStreamExt,
+    },
+};
 use jsonrpsee::client::Subscription;
 use sp_core::{
     storage::{
@@ -60,49 +67,110 @@ use sp_core::{
     },
     Bytes,
 };
+use sp_rpc::number::NumberOrHex;
 pub use sp_runtime::traits::SignedExtension;
+use sp_runtime::{
+    generic::Era,
+    traits::{
+        Hash,
+        Header,
+        SaturatedConversion,
+    },
+};
 pub use sp_version::RuntimeVersion;
-use std::marker::PhantomData;
+use std::{
+    marker::PhantomData,
+    ops::RangeInclusive,
+    sync::Arc,
+};
 
+mod encoder;
 mod error;
 mod events;
 pub mod extrinsic;
 mod frame;
 mod metadata;
+mod offline;
+mod progress;
+mod registry;
 mod rpc;
 mod runtimes;
 mod subscription;
+mod tx;
+mod upgrade;
+mod value;
 
 pub use crate::{
+    encoder::{
+        CallEncoder,
+        EncodeError,
+    },
     error::Error,
     events::{
+        BlockEvents,
+        EventRecord,
         EventsDecoder,
+        Raw,
         RawEvent,
+        UndecodedEvents,
     },
     extrinsic::{
+        ExtraContext,
+        Mortality,
         PairSigner,
+        SignOptions,
         SignedExtra,
         Signer,
         UncheckedExtrinsic,
     },
     frame::*,
     metadata::{
+        ArgumentExport,
+        CallExport,
+        ConstantExport,
+        ErrorExport,
+        EventExport,
+        Incompatibility,
         Metadata,
+        MetadataChange,
+        MetadataDiff,
         MetadataError,
+        MetadataExport,
+        MetadataItem,
+        ModuleExport,
+        StorageExport,
+    },
+    offline::OfflineMetadata,
+    progress::{
+        Inclusion,
+        TransactionProgress,
+    },
+    registry::{
+        TypeRegistry,
+        TypeRegistryError,
     },
     rpc::{
         BlockNumber,
         ExtrinsicSuccess,
         ReadProof,
         SystemProperties,
+        TransactionStatus,
     },
     runtimes::*,
     subscription::*,
     substrate_subxt_proc_macro::*,
+    tx::{
+        SignedTransaction,
+        TransactionBuilder,
+    },
+    upgrade::{
+        RuntimeUpgrade,
+        RuntimeUpgrades,
+    },
+    value::Value,
 };
 use crate::{
     frame::system::{
-        AccountStoreExt,
         Phase,
         System,
     },
@@ -110,6 +178,7 @@ use crate::{
         ChainBlock,
         Rpc,
     },
+    upgrade::RuntimeState,
 };
 
 /// ClientBuilder for constructing a Client.
@@ -119,6 +188,12 @@ pub struct ClientBuilder<T: Runtime> {
     url: Option<String>,
     client: Option<jsonrpsee::Client>,
     page_size: Option<u32>,
+    type_registry: Option<TypeRegistry>,
+    metadata: Option<Metadata>,
+    genesis_hash: Option<T::Hash>,
+    runtime_version: Option<RuntimeVersion>,
+    properties: Option<SystemProperties>,
+    mortality: Option<Mortality>,
 }
 
 impl<T: Runtime> ClientBuilder<T> {
@@ -129,6 +204,12 @@ impl<T: Runtime> ClientBuilder<T> {
             url: None,
             client: None,
             page_size: None,
+            type_registry: None,
+            metadata: None,
+            genesis_hash: None,
+            runtime_version: None,
+            properties: None,
+            mortality: None,
         }
     }
 
@@ -150,47 +231,122 @@ impl<T: Runtime> ClientBuilder<T> {
         self
     }
 
+    /// Set the type registry used to decode events.
+    pub fn set_type_registry(mut self, registry: TypeRegistry) -> Self {
+        self.type_registry = Some(registry);
+        self
+    }
+
+    /// Set the metadata instead of fetching it from the node.
+    pub fn set_metadata(mut self, metadata: Metadata) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+
+    /// Set the genesis hash instead of fetching it from the node.
+    pub fn set_genesis_hash(mut self, genesis_hash: T::Hash) -> Self {
+        self.genesis_hash = Some(genesis_hash);
+        self
+    }
+
+    /// Set the runtime version instead of fetching it from the node.
+    pub fn set_runtime_version(mut self, runtime_version: RuntimeVersion) -> Self {
+        self.runtime_version = Some(runtime_version);
+        self
+    }
+
+    /// Set the system properties instead of fetching them from the node.
+    pub fn set_properties(mut self, properties: SystemProperties) -> Self {
+        self.properties = Some(properties);
+        self
+    }
+
+    /// Set the mortality of the transactions signed by the client.
+    ///
+    /// Transactions are mortal for 64 blocks from the finalized head by default.
+    pub fn set_mortality(mut self, mortality: Mortality) -> Self {
+        self.mortality = Some(mortality);
+        self
+    }
+
+    /// Set the metadata, genesis hash, runtime version and system properties from
+    /// saved chain information.
+    ///
+    /// If no client or url is set, the client is offline: building it does not require
+    /// a running node, and every request to a node fails with `Error::Offline`. An
+    /// offline client can not fetch the finalized head to sign mortal transactions
+    /// from, so they have to be signed with a checkpoint, see
+    /// `TransactionBuilder::checkpoint`, or the client built with `Mortality::Immortal`.
+    pub fn set_offline_metadata(self, offline: OfflineMetadata<T::Hash>) -> Self {
+        self.set_metadata(offline.metadata)
+            .set_genesis_hash(offline.genesis_hash)
+            .set_runtime_version(offline.runtime_version)
+            .set_properties(offline.properties)
+    }
+
     /// Creates a new Client.
+    ///
+    /// Fetches the metadata, genesis hash, runtime version and system properties
+    /// that were not set from the node.
     pub async fn build(self) -> Result<Client<T>, Error> {
-        let client = if let Some(client) = self.client {
-            client
+        let offline = self.metadata.is_some()
+            && self.genesis_hash.is_some()
+            && self.runtime_version.is_some()
+            && self.properties.is_some();
+        let rpc = if let Some(client) = self.client {
+            Rpc::new(client)
+        } else if let (None, true) = (&self.url, offline) {
+            Rpc::offline()
         } else {
             let url = self.url.as_deref().unwrap_or("ws://127.0.0.1:9944");
             if url.starts_with("ws://") || url.starts_with("wss://") {
-                jsonrpsee::ws_client(url).await?
+                Rpc::new(jsonrpsee::ws_client(url).await?)
             } else {
-                jsonrpsee::http_client(url)
+                Rpc::new(jsonrpsee::http_client(url))
             }
         };
-        let rpc = Rpc::new(client);
         let (metadata, genesis_hash, runtime_version, properties) = future::join4(
-            rpc.metadata(),
-            rpc.genesis_hash(),
-            rpc.runtime_version(None),
-            rpc.system_properties(),
+            or_fetch(self.metadata, rpc.metadata(None)),
+            or_fetch(self.genesis_hash, rpc.genesis_hash()),
+            or_fetch(self.runtime_version, rpc.runtime_version(None)),
+            or_fetch(self.properties, rpc.system_properties()),
         )
         .await;
+        let runtime = Arc::new(RuntimeState::new(runtime_version?, metadata?));
         Ok(Client {
             rpc,
             genesis_hash: genesis_hash?,
-            metadata: metadata?,
+            runtime,
             properties: properties.unwrap_or_else(|_| Default::default()),
-            runtime_version: runtime_version?,
             _marker: PhantomData,
             page_size: self.page_size.unwrap_or(10),
+            type_registry: self.type_registry.unwrap_or_default(),
+            mortality: self.mortality.unwrap_or_default(),
         })
     }
 }
 
+/// Returns the value if it is set, otherwise fetches it.
+async fn or_fetch<V>(
+    value: Option<V>,
+    fetch: impl future::Future<Output = Result<V, Error>>,
+) -> Result<V, Error> {
+    match value {
+        Some(value) => Ok(value),
+        None => fetch.await,
+    }
+}
+
 /// Client to interface with a substrate node.
 pub struct Client<T: Runtime> {
     rpc: Rpc<T>,
     genesis_hash: T::Hash,
-    metadata: Metadata,
+    runtime: Arc<RuntimeState>,
     properties: SystemProperties,
-    runtime_version: RuntimeVersion,
     _marker: PhantomData<(fn() -> T::Signature, T::Extra)>,
     page_size: u32,
+    type_registry: TypeRegistry,
+    mortality: Mortality,
 }
 
 impl<T: Runtime> Clone for Client<T> {
@@ -198,11 +354,12 @@ impl<T: Runtime> Clone for Client<T> {
         Self {
             rpc: self.rpc.clone(),
             genesis_hash: self.genesis_hash,
-            metadata: self.metadata.clone(),
+            runtime: self.runtime.clone(),
             properties: self.properties.clone(),
-            runtime_version: self.runtime_version.clone(),
             _marker: PhantomData,
             page_size: self.page_size,
+            type_registry: self.type_registry.clone(),
+            mortality: self.mortality,
         }
     }
 }
@@ -210,6 +367,7 @@ impl<T: Runtime> Clone for Client<T> {
 /// Iterates over key value pairs in a map.
 pub struct KeyIter<T: Runtime, F: Store<T>> {
     client: Client<T>,
+    metadata: Arc<Metadata>,
     _marker: PhantomData<F>,
     count: u32,
     hash: T::Hash,
@@ -253,6 +411,21 @@ impl<T: Runtime, F: Store<T>> KeyIter<T, F> {
     }
 }
 
+impl<T: Runtime, F: StoreKey<T>> KeyIter<T, F> {
+    /// Returns the next key value pair from a map, with the map keys decoded.
+    ///
+    /// Fails for maps whose hasher does not retain the keys, use `next` to iterate
+    /// over those.
+    pub async fn next_decoded(&mut self) -> Result<Option<(F::Key, F::Returns)>, Error> {
+        if let Some((key, value)) = self.next().await? {
+            let key = F::decode_key(&self.metadata, &key)?;
+            Ok(Some((key, value)))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
 impl<T: Runtime> Client<T> {
     /// Returns the genesis hash.
     pub fn genesis(&self) -> &T::Hash {
@@ -260,8 +433,88 @@ impl<T: Runtime> Client<T> {
     }
 
     /// Returns the chain metadata.
-    pub fn metadata(&self) -> &Metadata {
-        &self.metadata
+    ///
+    /// The metadata is replaced when the runtime is upgraded.
+    pub fn metadata(&self) -> Arc<Metadata> {
+        self.runtime.metadata()
+    }
+
+    /// Returns the runtime version.
+    pub fn runtime_version(&self) -> RuntimeVersion {
+        self.runtime.runtime_version()
+    }
+
+    /// Returns the runtime version and metadata of the runtime of a block.
+    ///
+    /// The metadata is fetched at the block the first time a spec version is seen,
+    /// and cached for all later blocks of that spec version.
+    pub async fn runtime_at(
+        &self,
+        hash: T::Hash,
+    ) -> Result<(RuntimeVersion, Arc<Metadata>), Error> {
+        let runtime_version = self.rpc.runtime_version(Some(hash)).await?;
+        let metadata = self
+            .metadata_of_runtime(hash, runtime_version.spec_version)
+            .await?;
+        Ok((runtime_version, metadata))
+    }
+
+    /// Returns the cached metadata of a spec version, or fetches it at a block of that
+    /// spec version.
+    async fn metadata_of_runtime(
+        &self,
+        hash: T::Hash,
+        spec_version: u32,
+    ) -> Result<Arc<Metadata>, Error> {
+        match self.runtime.metadata_of(spec_version) {
+            Some(metadata) => Ok(metadata),
+            None => {
+                let metadata = self.rpc.metadata(Some(hash)).await?;
+                Ok(self.runtime.cache(spec_version, metadata))
+            }
+        }
+    }
+
+    /// Returns the metadata of the runtime of a block.
+    pub async fn metadata_at(&self, hash: T::Hash) -> Result<Arc<Metadata>, Error> {
+        Ok(self.runtime_at(hash).await?.1)
+    }
+
+    /// Returns the metadata of a block, or the current metadata if no hash is given.
+    async fn metadata_for(&self, hash: Option<T::Hash>) -> Result<Arc<Metadata>, Error> {
+        match hash {
+            Some(hash) => self.metadata_at(hash).await,
+            None => Ok(self.metadata()),
+        }
+    }
+
+    /// Returns a future which follows the runtime upgrades of the chain until every
+    /// clone of the client is dropped.
+    ///
+    /// The future is run on the executor of the application, e.g. with
+    /// `async_std::task::spawn(client.follow_runtime_upgrades())`. While it runs, the
+    /// client fetches the metadata of every new runtime, so that transactions are
+    /// signed with the current runtime version and events are decoded with the
+    /// current metadata. Completes right away if the runtime upgrades are followed
+    /// already.
+    pub fn follow_runtime_upgrades(
+        &self,
+    ) -> impl future::Future<Output = ()> + Send + 'static {
+        let following = RuntimeState::start_following(&self.runtime);
+        let rpc = self.rpc.clone();
+        async move {
+            if let Some(following) = following {
+                upgrade::follow_runtime_upgrades(rpc, following).await
+            }
+        }
+    }
+
+    /// Returns a stream of the runtime upgrades of the chain.
+    ///
+    /// The client is updated before an upgrade is reported. The stream ends right
+    /// away unless the future returned by `follow_runtime_upgrades` is running.
+    pub fn runtime_upgrades(&self) -> RuntimeUpgrades {
+        self.runtime.subscribe()
     }
 
     /// Returns the system properties
@@ -269,6 +522,37 @@ impl<T: Runtime> Client<T> {
         &self.properties
     }
 
+    /// Returns the chain information fetched on startup, to be saved and used to
+    /// build a client without fetching it again.
+    pub fn offline_metadata(&self) -> OfflineMetadata<T::Hash> {
+        let (runtime_version, metadata) = self.runtime.current();
+        OfflineMetadata {
+            genesis_hash: self.genesis_hash,
+            runtime_version,
+            properties: self.properties.clone(),
+            metadata: (*metadata).clone(),
+        }
+    }
+
+    /// Checks compiled `Call`, `Store` and `Event` types against the metadata.
+    ///
+    /// Several types are checked at once by passing a tuple, e.g.
+    /// `check_compatibility::<(TransferCall<'static, T>, TransferEvent<T>)>()`. Returns
+    /// every mismatch found.
+    pub fn check_compatibility<C: CheckCompatibility<T>>(&self) -> Result<(), Error> {
+        let incompatibilities = C::check_compatibility(&self.metadata());
+        if incompatibilities.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::Incompatible(incompatibilities))
+        }
+    }
+
+    /// Returns the value of a module constant.
+    pub fn constant<C: Constant<T>>(&self) -> Result<C::Returns, Error> {
+        Ok(C::value(&self.metadata())?)
+    }
+
     /// Fetch the value under an unhashed storage key
     pub async fn fetch_unhashed<V: Decode>(
         &self,
@@ -288,7 +572,7 @@ impl<T: Runtime> Client<T> {
         store: &F,
         hash: Option<T::Hash>,
     ) -> Result<Option<F::Returns>, Error> {
-        let key = store.key(&self.metadata)?;
+        let key = store.key(&self.metadata_for(hash).await?)?;
         self.fetch_unhashed::<F::Returns>(key, hash).await
     }
 
@@ -298,10 +582,12 @@ impl<T: Runtime> Client<T> {
         store: &F,
         hash: Option<T::Hash>,
     ) -> Result<F::Returns, Error> {
-        if let Some(data) = self.fetch(store, hash).await? {
+        let metadata = self.metadata_for(hash).await?;
+        let key = store.key(&metadata)?;
+        if let Some(data) = self.fetch_unhashed::<F::Returns>(key, hash).await? {
             Ok(data)
         } else {
-            Ok(store.default(&self.metadata)?)
+            Ok(store.default(&metadata)?)
         }
     }
 
@@ -319,6 +605,7 @@ impl<T: Runtime> Client<T> {
         };
         Ok(KeyIter {
             client: self.clone(),
+            metadata: self.metadata_at(hash).await?,
             hash,
             count: self.page_size,
             start_key: None,
@@ -336,7 +623,7 @@ impl<T: Runtime> Client<T> {
         start_key: Option<StorageKey>,
         hash: Option<T::Hash>,
     ) -> Result<Vec<StorageKey>, Error> {
-        let prefix = <F as Store<T>>::prefix(&self.metadata)?;
+        let prefix = <F as Store<T>>::prefix(&self.metadata_for(hash).await?)?;
         let keys = self
             .rpc
             .storage_keys_paged(Some(prefix), count, start_key, hash)
@@ -400,7 +687,128 @@ impl<T: Runtime> Client<T> {
         Ok(proof)
     }
 
+    /// Fetch and decode the events of a block.
+    ///
+    /// The events are decoded with the metadata of the runtime of the block, keeping
+    /// the type sizes registered with the decoder.
+    pub async fn events_at(
+        &self,
+        hash: T::Hash,
+        decoder: &EventsDecoder<T>,
+    ) -> Result<BlockEvents<T>, Error> {
+        let (runtime_version, metadata) = self.runtime_at(hash).await?;
+        let decoder = decoder.for_runtime(
+            metadata,
+            &self.type_registry,
+            runtime_version.spec_version,
+        );
+        self.decode_events_at(hash, &decoder).await
+    }
+
+    /// Fetch and decode the events of a range of blocks by block number.
+    ///
+    /// The events of each block are decoded with the metadata of its runtime. The
+    /// hashes, runtime versions and events of several blocks are requested at once.
+    pub async fn events_range(
+        &self,
+        range: RangeInclusive<T::BlockNumber>,
+        decoder: &EventsDecoder<T>,
+    ) -> Result<Vec<BlockEvents<T>>, Error> {
+        const CONCURRENT_BLOCKS: usize = 16;
+        let (start, end) = range.into_inner();
+        let numbers = start.saturated_into::<u64>()..=end.saturated_into::<u64>();
+        let fetched = stream::iter(numbers.map(|number| {
+            async move {
+                let hash = self
+                    .block_hash(Some(NumberOrHex::Number(number).into()))
+                    .await?
+                    .ok_or_else(|| Error::Other(format!("Block {} not found", number)))?;
+                let (runtime_version, data) = future::try_join(
+                    self.rpc.runtime_version(Some(hash)),
+                    self.rpc.events(hash),
+                )
+                .await?;
+                Ok::<_, Error>((hash, runtime_version.spec_version, data))
+            }
+        }))
+        .buffered(CONCURRENT_BLOCKS)
+        .try_collect::<Vec<_>>()
+        .await?;
+        let mut blocks = Vec::new();
+        let mut runtime_decoder = None;
+        for (hash, spec_version, data) in fetched {
+            // only the first block of a runtime which is not cached fetches its metadata
+            let metadata = self.metadata_of_runtime(hash, spec_version).await?;
+            let block_decoder = self.runtime_decoder(
+                decoder,
+                spec_version,
+                metadata,
+                &mut runtime_decoder,
+            );
+            let events = match data {
+                Some(data) => block_decoder.decode_events(&mut &data.0[..])?,
+                None => Vec::new(),
+            };
+            blocks.push(BlockEvents::new(hash, events));
+        }
+        Ok(blocks)
+    }
+
+    /// Returns a decoder for the events of a block.
+    ///
+    /// The decoder for the runtime of the previous block is reused if the block has the
+    /// same spec version, otherwise it is replaced with one for the runtime of the
+    /// block.
+    pub(crate) async fn decoder_at<'a>(
+        &self,
+        hash: T::Hash,
+        decoder: &EventsDecoder<T>,
+        runtime_decoder: &'a mut Option<(u32, EventsDecoder<T>)>,
+    ) -> Result<&'a EventsDecoder<T>, Error> {
+        let (runtime_version, metadata) = self.runtime_at(hash).await?;
+        Ok(self.runtime_decoder(
+            decoder,
+            runtime_version.spec_version,
+            metadata,
+            runtime_decoder,
+        ))
+    }
+
+    /// Returns the decoder for a spec version, replacing the decoder of the previous
+    /// runtime if the spec version differs.
+    fn runtime_decoder<'a>(
+        &self,
+        decoder: &EventsDecoder<T>,
+        spec_version: u32,
+        metadata: Arc<Metadata>,
+        runtime_decoder: &'a mut Option<(u32, EventsDecoder<T>)>,
+    ) -> &'a EventsDecoder<T> {
+        if !matches!(runtime_decoder, Some((version, _)) if *version == spec_version) {
+            let block_decoder =
+                decoder.for_runtime(metadata, &self.type_registry, spec_version);
+            *runtime_decoder = Some((spec_version, block_decoder));
+        }
+        let (_, block_decoder) =
+            runtime_decoder.as_ref().expect("decoder set above; qed");
+        block_decoder
+    }
+
+    async fn decode_events_at(
+        &self,
+        hash: T::Hash,
+        decoder: &EventsDecoder<T>,
+    ) -> Result<BlockEvents<T>, Error> {
+        let events = match self.rpc.events(hash).await? {
+            Some(data) => decoder.decode_events(&mut &data.0[..])?,
+            None => Vec::new(),
+        };
+        Ok(BlockEvents::new(hash, events))
+    }
+
     /// Subscribe to events.
+    ///
+    /// The change sets contain the encoded events, use `event_subscription` to decode
+    /// them with the metadata of the runtime of each block.
     pub async fn subscribe_events(
         &self,
     ) -> Result<Subscription<StorageChangeSet<T::Hash>>, Error> {
@@ -408,6 +816,16 @@ impl<T: Runtime> Client<T> {
         Ok(events)
     }
 
+    /// Subscribe to events, decoding the events of each block with the metadata of
+    /// its runtime and the type sizes registered with the decoder.
+    pub async fn event_subscription(
+        &self,
+        decoder: EventsDecoder<T>,
+    ) -> Result<EventSubscription<T>, Error> {
+        let events = self.rpc.subscribe_events().await?;
+        Ok(EventSubscription::for_client(events, decoder, self.clone()))
+    }
+
     /// Subscribe to new blocks.
     pub async fn subscribe_blocks(&self) -> Result<Subscription<T::Header>, Error> {
         let headers = self.rpc.subscribe_blocks().await?;
@@ -430,6 +848,34 @@ impl<T: Runtime> Client<T> {
             .and_then(|module| module.call(C::FUNCTION, call))?)
     }
 
+    /// Returns a call encoder for building calls from dynamic values.
+    ///
+    /// The types of the modules of the runtime are registered, see
+    /// `Runtime::call_encoder`, as well as the address of the runtime as the `Source` of
+    /// calls.
+    pub fn call_encoder(&self) -> CallEncoder<T> {
+        let (runtime_version, metadata) = self.runtime.current();
+        let mut encoder = CallEncoder::new((*metadata).clone());
+        T::call_encoder(&mut encoder);
+        encoder.register_type_decoder::<T::Address>("Source");
+        encoder.register_type_registry(&self.type_registry, runtime_version.spec_version);
+        encoder
+    }
+
+    /// Encodes a call by module and call name from one value per argument.
+    ///
+    /// The values are checked against the call arguments in the metadata, so a wrong
+    /// number of arguments or a value of the wrong type is reported before anything is
+    /// signed. The encoded call can be signed with `extrinsic::create_signed`.
+    pub fn dynamic_call(
+        &self,
+        module: &str,
+        call: &str,
+        args: Vec<Value>,
+    ) -> Result<Encoded, Error> {
+        self.call_encoder().encode_call(module, call, &args)
+    }
+
     /// Creates an unsigned extrinsic.
     pub fn create_unsigned<C: Call<T> + Send + Sync>(
         &self,
@@ -439,38 +885,61 @@ impl<T: Runtime> Client<T> {
         Ok(extrinsic::create_unsigned::<T>(call))
     }
 
+    /// Returns a builder for signing a call with explicit options, e.g.
+    /// `client.tx(call).nonce(nonce).tip(tip).mortal(64).sign(&signer).await?`.
+    pub fn tx<C: Call<T> + Send + Sync>(&self, call: C) -> TransactionBuilder<T, C> {
+        TransactionBuilder::new(self, call)
+    }
+
     /// Creates a signed extrinsic.
+    ///
+    /// The extrinsic is mortal, starting at the finalized head, unless the client was
+    /// built with `Mortality::Immortal`. Offline clients have to be built immortal, or
+    /// sign with a checkpoint using `Client::tx`.
     pub async fn create_signed<C: Call<T> + Send + Sync>(
         &self,
         call: C,
         signer: &(dyn Signer<T> + Send + Sync),
-    ) -> Result<UncheckedExtrinsic<T>, Error>
-    where
-        <<T::Extra as SignedExtra<T>>::Extra as SignedExtension>::AdditionalSigned:
-            Send + Sync,
-    {
-        let account_nonce = if let Some(nonce) = signer.nonce() {
-            nonce
+    ) -> Result<UncheckedExtrinsic<T>, Error> {
+        let signed = self.tx(call).sign(signer).await?;
+        Ok(signed.into_extrinsic())
+    }
+
+    /// Returns a mortal era starting at the checkpoint, or at the finalized head if
+    /// there is none, and the hash of the block it starts at.
+    async fn mortal_era(
+        &self,
+        period: u64,
+        checkpoint: Option<(T::BlockNumber, T::Hash)>,
+    ) -> Result<(Era, T::Hash), Error> {
+        let (number, hash) = if let Some(checkpoint) = checkpoint {
+            checkpoint
         } else {
-            self.account(signer.account_id(), None).await?.nonce
+            let hash = self.finalized_head().await?;
+            let header = self.header(Some(hash)).await?.ok_or_else(|| {
+                Error::Other(format!("Finalized block {:?} not found", hash))
+            })?;
+            (*header.number(), hash)
         };
-        let call = self.encode(call)?;
-        let signed = extrinsic::create_signed(
-            &self.runtime_version,
-            self.genesis_hash,
-            account_nonce,
-            call,
-            signer,
-        )
-        .await?;
-        Ok(signed)
+        let number = number.saturated_into::<u64>();
+        let era = Era::mortal(period, number);
+        let birth = era.birth(number);
+        if birth == number {
+            return Ok((era, hash))
+        }
+        let hash = self
+            .block_hash(Some(NumberOrHex::Number(birth).into()))
+            .await?
+            .ok_or_else(|| Error::Other(format!("Block {} not found", birth)))?;
+        Ok((era, hash))
     }
 
     /// Returns an events decoder for a call.
     pub fn events_decoder<C: Call<T>>(&self) -> EventsDecoder<T> {
-        let metadata = self.metadata().clone();
-        let mut decoder = EventsDecoder::new(metadata);
+        let (runtime_version, metadata) = self.runtime.current();
+        let mut decoder = EventsDecoder::new((*metadata).clone());
         C::events_decoder(&mut decoder);
+        decoder.register_type_registry(&self.type_registry, runtime_version.spec_version);
         decoder
     }
 
@@ -483,26 +952,45 @@ impl<T: Runtime> Client<T> {
     }
 
     /// Create and submit an extrinsic and return corresponding Event if successful
+    ///
+    /// Returns as soon as the extrinsic is included in a block, which may still be
+    /// retracted. Use `submit_and_watch_progress` to follow the extrinsic through
+    /// reorgs.
     pub async fn submit_and_watch_extrinsic(
         &self,
         extrinsic: UncheckedExtrinsic<T>,
         decoder: EventsDecoder<T>,
     ) -> Result<ExtrinsicSuccess<T>, Error> {
-        self.rpc
-            .submit_and_watch_extrinsic(extrinsic, decoder)
+        self.submit_and_watch_progress(extrinsic, decoder)
+            .await?
+            .wait_for_in_block()
             .await
     }
 
+    /// Submits an extrinsic and returns the progress of the transaction, e.g. for
+    /// waiting until it is finalized.
+    pub async fn submit_and_watch_progress(
+        &self,
+        extrinsic: UncheckedExtrinsic<T>,
+        decoder: EventsDecoder<T>,
+    ) -> Result<TransactionProgress<T>, Error> {
+        let ext_hash = T::Hashing::hash_of(&extrinsic);
+        log::info!("Submitting Extrinsic `{:?}`", ext_hash);
+        let subscription = self.rpc.watch_extrinsic(extrinsic).await?;
+        Ok(TransactionProgress::new(
+            self.clone(),
+            subscription,
+            ext_hash,
+            decoder,
+        ))
+    }
+
     /// Submits a transaction to the chain.
     pub async fn submit<C: Call<T> + Send + Sync>(
         &self,
         call: C,
         signer: &(dyn Signer<T> + Send + Sync),
-    ) -> Result<T::Hash, Error>
-    where
-        <<T::Extra as SignedExtra<T>>::Extra as SignedExtension>::AdditionalSigned:
-            Send + Sync,
-    {
+    ) -> Result<T::Hash, Error> {
         let extrinsic = self.create_signed(call, signer).await?;
         self.submit_extrinsic(extrinsic).await
     }
@@ -512,11 +1000,7 @@ impl<T: Runtime> Client<T> {
         &self,
         call: C,
         signer: &(dyn Signer<T> + Send + Sync),
-    ) -> Result<ExtrinsicSuccess<T>, Error>
-    where
-        <<T::Extra as SignedExtra<T>>::Extra as SignedExtension>::AdditionalSigned:
-            Send + Sync,
-    {
+    ) -> Result<ExtrinsicSuccess<T>, Error> {
         let extrinsic = self.create_signed(call, signer).await?;
         let decoder = self.events_decoder::<C>();
         self.submit_and_watch_extrinsic(extrinsic, decoder).await
@@ -572,6 +1056,7 @@ impl codec::Encode for Encoded {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::frame::system::AccountStoreExt;
     use sp_core::storage::{
         well_known_keys,
         StorageKey,
@@ -687,6 +1172,95 @@ mod tests {
             .unwrap();
     }
 
+    #[async_std::test]
+    async fn test_tx_builder() {
+        let signer = PairSigner::new(AccountKeyring::Alice.pair());
+        let dest = AccountKeyring::Bob.to_account_id().into();
+
+        let (client, _) = test_client().await;
+        let nonce = client
+            .account(&AccountKeyring::Alice.to_account_id(), None)
+            .await
+            .unwrap()
+            .nonce;
+        let signed = client
+            .tx(balances::TransferCall {
+                to: &dest,
+                amount: 10_000,
+            })
+            .nonce(nonce)
+            .tip(5)
+            .mortal(64)
+            .sign(&signer)
+            .await
+            .unwrap();
+        assert_eq!(signed.context().nonce, nonce);
+        assert_eq!(signed.context().tip, 5);
+        assert!(!signed.context().era.is_immortal());
+
+        signed.dry_run().await.unwrap();
+        let hash = signed.hash();
+        assert_eq!(signed.submit().await.unwrap(), hash);
+    }
+
+    #[async_std::test]
+    async fn test_tx_wait_for_finalized() {
+        let signer = PairSigner::new(AccountKeyring::Alice.pair());
+        let dest = AccountKeyring::Bob.to_account_id().into();
+
+        let (client, _) = test_client().await;
+        let signed = client
+            .tx(balances::TransferCall {
+                to: &dest,
+                amount: 10_000,
+            })
+            .sign(&signer)
+            .await
+            .unwrap();
+        let hash = signed.hash();
+        let mut progress = signed.progress().await.unwrap();
+        assert_eq!(progress.extrinsic_hash(), hash);
+        assert_eq!(progress.next().await, Some(TransactionStatus::Ready));
+
+        let success = progress.wait_for_finalized().await.unwrap();
+        let event = success.find_event::<balances::TransferEvent<_>>().unwrap();
+        assert_eq!(
+            event,
+            Some(balances::TransferEvent {
+                from: AccountKeyring::Alice.to_account_id(),
+                to: AccountKeyring::Bob.to_account_id(),
+                amount: 10_000,
+            })
+        );
+    }
+
+    #[async_std::test]
+    async fn test_tx_progress_stream() {
+        use futures::StreamExt;
+
+        let signer = PairSigner::new(AccountKeyring::Alice.pair());
+        let dest = AccountKeyring::Bob.to_account_id().into();
+
+        let (client, _) = test_client().await;
+        let progress = client
+            .tx(balances::TransferCall {
+                to: &dest,
+                amount: 10_000,
+            })
+            .sign(&signer)
+            .await
+            .unwrap()
+            .progress()
+            .await
+            .unwrap();
+        let statuses = progress.collect::<Vec<_>>().await;
+        assert_eq!(statuses.first(), Some(&TransactionStatus::Ready));
+        assert!(matches!(
+            statuses.last(),
+            Some(TransactionStatus::Finalized(_))
+        ));
+    }
+
     #[async_std::test]
     async fn test_getting_hash() {
         let (client, _) = test_client().await;
@@ -750,4 +1324,16 @@ mod tests {
         }
         assert_eq!(i, 4);
     }
+
+    #[async_std::test]
+    async fn test_iter_decoded() {
+        let (client, _) = test_client().await;
+        let mut iter = client.iter::<system::AccountStore<_>>(None).await.unwrap();
+        let mut i = 0;
+        while let Some((account, _)) = iter.next_decoded().await.unwrap() {
+            assert!(client.account(&account, None).await.is_ok());
+            i += 1;
+        }
+        assert_eq!(i, 4);
+    }
 }
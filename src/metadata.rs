@@ -17,20 +17,33 @@
 use std::{
     collections::HashMap,
     convert::TryFrom,
+    fmt,
     marker::PhantomData,
     str::FromStr,
+    sync::Arc,
 };
 
 use codec::{
     Decode,
     Encode,
     Error as CodecError,
+    Input,
+    Output,
+};
+use serde::{
+    de::Error as _,
+    Deserialize,
+    Deserializer,
+    Serialize,
+    Serializer,
 };
 
 use frame_metadata::{
     DecodeDifferent,
+    ExtrinsicMetadata,
     RuntimeMetadata,
     RuntimeMetadataPrefixed,
+    RuntimeMetadataV12,
     StorageEntryModifier,
     StorageEntryType,
     StorageHasher,
@@ -40,6 +53,34 @@ use sp_core::storage::StorageKey;
 
 use crate::Encoded;
 
+mod compat;
+mod diff;
+mod export;
+mod legacy;
+
+use self::legacy::{
+    RuntimeMetadataV10,
+    RuntimeMetadataV11,
+};
+pub use self::{
+    compat::Incompatibility,
+    diff::{
+        MetadataChange,
+        MetadataDiff,
+        MetadataItem,
+    },
+    export::{
+        ArgumentExport,
+        CallExport,
+        ConstantExport,
+        ErrorExport,
+        EventExport,
+        MetadataExport,
+        ModuleExport,
+        StorageExport,
+    },
+};
+
 /// Metadata error.
 #[derive(Debug, thiserror::Error)]
 pub enum MetadataError {
@@ -55,10 +96,16 @@ pub enum MetadataError {
     /// Call is not in metadata.
     #[error("Call {0} not found")]
     CallNotFound(&'static str),
+    /// Call is not in metadata.
+    #[error("Call {0} not found")]
+    CallNameNotFound(String),
     /// Event is not in metadata.
     #[error("Event {0} not found")]
     EventNotFound(u8),
     /// Event is not in metadata.
+    #[error("Event {0} not found")]
+    EventNameNotFound(String),
+    /// Event is not in metadata.
     #[error("Error {0} not found")]
     ErrorNotFound(u8),
     /// Storage is not in metadata.
@@ -67,18 +114,40 @@ pub enum MetadataError {
     /// Storage type does not match requested type.
     #[error("Storage type error")]
     StorageTypeError,
+    /// No storage entry has the prefix of the key.
+    #[error("No storage entry found for key")]
+    StorageKeyNotFound,
+    /// The key does not start with the prefix of the storage entry.
+    #[error("Storage key does not belong to the storage entry")]
+    StorageKeyMismatch,
+    /// The key was hashed with a hasher which does not retain it.
+    #[error("Storage key hashed with {0:?} cannot be decoded")]
+    OpaqueStorageKey(StorageHasher),
+    /// The key could not be decoded.
+    #[error("Failed to decode storage key: {0}")]
+    StorageKeyDecode(CodecError),
     /// Default error.
     #[error("Failed to decode default: {0}")]
     DefaultError(CodecError),
+    /// Constant is not in metadata.
+    #[error("Constant {0} not found")]
+    ConstantNotFound(String),
+    /// Constant value could not be decoded.
+    #[error("Failed to decode constant value: {0}")]
+    ConstantValueError(CodecError),
+    /// Metadata could not be decoded.
+    #[error("Failed to decode metadata: {0}")]
+    Decode(CodecError),
 }
 
 /// Runtime metadata.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone)]
 pub struct Metadata {
     modules: HashMap<String, ModuleMetadata>,
     modules_with_calls: HashMap<String, ModuleWithCalls>,
     modules_with_events: HashMap<String, ModuleWithEvents>,
     modules_with_errors: HashMap<String, ModuleWithErrors>,
+    encoded: Arc<Vec<u8>>,
 }
 
 impl Metadata {
@@ -93,6 +162,19 @@ impl Metadata {
             .ok_or(MetadataError::ModuleNotFound(name))
     }
 
+    /// Returns the module and storage entry a storage key belongs to, by matching the
+    /// hashed prefixes of the module and storage entry.
+    pub fn storage_for_key(
+        &self,
+        key: &StorageKey,
+    ) -> Result<(&ModuleMetadata, &StorageMetadata), MetadataError> {
+        self.modules
+            .values()
+            .flat_map(|module| module.storage.values().map(move |entry| (module, entry)))
+            .find(|(_, entry)| key.0.starts_with(&entry.prefix().0))
+            .ok_or(MetadataError::StorageKeyNotFound)
+    }
+
     /// Returns `ModuleWithCalls`.
     pub fn module_with_calls<S>(&self, name: S) -> Result<&ModuleWithCalls, MetadataError>
     where
@@ -120,6 +202,24 @@ impl Metadata {
             .ok_or(MetadataError::ModuleIndexNotFound(module_index))
     }
 
+    /// Returns the `ModuleEventMetadata` of an event by module and event name.
+    pub fn event<S>(
+        &self,
+        module: S,
+        event: &str,
+    ) -> Result<&ModuleEventMetadata, MetadataError>
+    where
+        S: ToString,
+    {
+        let module = module.to_string();
+        self.modules_with_events
+            .get(&module)
+            .ok_or(MetadataError::ModuleNotFound(module))?
+            .events()
+            .find(|e| e.name == event)
+            .ok_or_else(|| MetadataError::EventNameNotFound(event.to_string()))
+    }
+
     /// Returns `ModuleWithErrors`.
     pub fn module_with_errors(
         &self,
@@ -166,21 +266,74 @@ pub struct ModuleMetadata {
     index: u8,
     name: String,
     storage: HashMap<String, StorageMetadata>,
-    // constants
+    constants: HashMap<String, ModuleConstantMetadata>,
 }
 
 impl ModuleMetadata {
+    /// Returns the name of the module.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
     pub fn storage(&self, key: &'static str) -> Result<&StorageMetadata, MetadataError> {
         self.storage
             .get(key)
             .ok_or(MetadataError::StorageNotFound(key))
     }
+
+    /// Returns the constant with the given name.
+    pub fn constant(&self, name: &str) -> Result<&ModuleConstantMetadata, MetadataError> {
+        self.constants
+            .get(name)
+            .ok_or_else(|| MetadataError::ConstantNotFound(name.to_string()))
+    }
+
+    /// Returns the constants of the module.
+    pub fn constants(&self) -> impl Iterator<Item = &ModuleConstantMetadata> {
+        self.constants.values()
+    }
+}
+
+/// Metadata of a module constant.
+#[derive(Clone, Debug)]
+pub struct ModuleConstantMetadata {
+    name: String,
+    ty: String,
+    value: Vec<u8>,
+    documentation: Vec<String>,
+}
+
+impl ModuleConstantMetadata {
+    /// Returns the name of the constant.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the name of the type of the constant.
+    pub fn ty(&self) -> &str {
+        &self.ty
+    }
+
+    /// Returns the encoded value of the constant.
+    pub fn value(&self) -> &[u8] {
+        &self.value
+    }
+
+    /// Returns the documentation of the constant.
+    pub fn documentation(&self) -> &[String] {
+        &self.documentation
+    }
+
+    /// Decodes the value of the constant.
+    pub fn value_as<V: Decode>(&self) -> Result<V, MetadataError> {
+        Decode::decode(&mut &self.value[..]).map_err(MetadataError::ConstantValueError)
+    }
 }
 
 #[derive(Clone, Debug)]
 pub struct ModuleWithCalls {
     index: u8,
-    calls: HashMap<String, u8>,
+    calls: HashMap<String, CallMetadata>,
 }
 
 impl ModuleWithCalls {
@@ -189,14 +342,86 @@ impl ModuleWithCalls {
         function: &'static str,
         params: T,
     ) -> Result<Encoded, MetadataError> {
-        let fn_index = self
+        let call = self
             .calls
             .get(function)
             .ok_or(MetadataError::CallNotFound(function))?;
-        let mut bytes = vec![self.index, *fn_index];
+        let mut bytes = vec![self.index, call.index];
         bytes.extend(params.encode());
         Ok(Encoded(bytes))
     }
+
+    /// Returns the index of the module in the outer call enum.
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+
+    /// Returns the metadata of all calls of the module.
+    pub fn calls(&self) -> impl Iterator<Item = &CallMetadata> {
+        self.calls.values()
+    }
+
+    /// Returns the `CallMetadata` of a call by name.
+    pub fn call_metadata(&self, name: &str) -> Result<&CallMetadata, MetadataError> {
+        self.calls
+            .get(name)
+            .ok_or_else(|| MetadataError::CallNameNotFound(name.to_string()))
+    }
+}
+
+/// Metadata of a dispatchable call.
+#[derive(Clone, Debug)]
+pub struct CallMetadata {
+    index: u8,
+    name: String,
+    arguments: Vec<CallArgMetadata>,
+    documentation: Vec<String>,
+}
+
+impl CallMetadata {
+    /// Returns the index of the call within its module.
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+
+    /// Returns the name of the call.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the arguments of the call, in encoding order.
+    pub fn arguments(&self) -> &[CallArgMetadata] {
+        &self.arguments
+    }
+
+    /// Returns the documentation of the call.
+    pub fn documentation(&self) -> &[String] {
+        &self.documentation
+    }
+}
+
+/// Metadata of a call argument.
+#[derive(Clone, Debug)]
+pub struct CallArgMetadata {
+    name: String,
+    ty: String,
+}
+
+impl CallArgMetadata {
+    /// Returns the name of the argument.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the type name of the argument, as found in the metadata.
+    pub fn ty(&self) -> &str {
+        &self.ty
+    }
+
+    /// Parses the type name of the argument.
+    pub fn arg_type(&self) -> Result<EventArg, ConversionError> {
+        self.ty.parse()
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -226,7 +451,7 @@ impl ModuleWithEvents {
 pub struct ModuleWithErrors {
     index: u8,
     name: String,
-    errors: HashMap<u8, String>,
+    errors: HashMap<u8, ModuleErrorMetadata>,
 }
 
 impl ModuleWithErrors {
@@ -235,10 +460,42 @@ impl ModuleWithErrors {
     }
 
     pub fn error(&self, index: u8) -> Result<&String, MetadataError> {
+        self.error_metadata(index).map(|error| &error.name)
+    }
+
+    /// Returns the `ModuleErrorMetadata` of an error by index.
+    pub fn error_metadata(
+        &self,
+        index: u8,
+    ) -> Result<&ModuleErrorMetadata, MetadataError> {
         self.errors
             .get(&index)
             .ok_or(MetadataError::ErrorNotFound(index))
     }
+
+    /// Returns the metadata of all errors of the module.
+    pub fn errors(&self) -> impl Iterator<Item = &ModuleErrorMetadata> {
+        self.errors.values()
+    }
+}
+
+/// Metadata of a module error.
+#[derive(Clone, Debug)]
+pub struct ModuleErrorMetadata {
+    name: String,
+    documentation: Vec<String>,
+}
+
+impl ModuleErrorMetadata {
+    /// Returns the name of the error.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the documentation of the error.
+    pub fn documentation(&self) -> &[String] {
+        &self.documentation
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -248,9 +505,15 @@ pub struct StorageMetadata {
     modifier: StorageEntryModifier,
     ty: StorageEntryType,
     default: Vec<u8>,
+    documentation: Vec<String>,
 }
 
 impl StorageMetadata {
+    /// Returns the name of the storage entry.
+    pub fn name(&self) -> &str {
+        &self.storage_prefix
+    }
+
     pub fn prefix(&self) -> StorageKey {
         let mut bytes = sp_core::twox_128(self.module_prefix.as_bytes()).to_vec();
         bytes.extend(&sp_core::twox_128(self.storage_prefix.as_bytes())[..]);
@@ -261,6 +524,11 @@ impl StorageMetadata {
         Decode::decode(&mut &self.default[..]).map_err(MetadataError::DefaultError)
     }
 
+    /// Returns the documentation of the storage entry.
+    pub fn documentation(&self) -> &[String] {
+        &self.documentation
+    }
+
     pub fn hash(hasher: &StorageHasher, bytes: &[u8]) -> Vec<u8> {
         match hasher {
             StorageHasher::Identity => bytes.to_vec(),
@@ -290,6 +558,25 @@ impl StorageMetadata {
         Self::hash(hasher, &key.encode())
     }
 
+    /// Decodes a key from the start of `input`, if the hasher appends the key to the
+    /// hash.
+    pub fn unhash_key<K: Decode>(
+        hasher: &StorageHasher,
+        input: &mut &[u8],
+    ) -> Result<K, MetadataError> {
+        let hash_len = match hasher {
+            StorageHasher::Identity => 0,
+            StorageHasher::Blake2_128Concat => 16,
+            StorageHasher::Twox64Concat => 8,
+            hasher => return Err(MetadataError::OpaqueStorageKey(hasher.clone())),
+        };
+        if input.len() < hash_len {
+            return Err(MetadataError::StorageKeyDecode("Key too short".into()))
+        }
+        *input = &input[hash_len..];
+        K::decode(input).map_err(MetadataError::StorageKeyDecode)
+    }
+
     pub fn plain(&self) -> Result<StoragePlain, MetadataError> {
         match &self.ty {
             StorageEntryType::Plain(_) => {
@@ -344,6 +631,12 @@ impl StoragePlain {
     pub fn key(&self) -> StorageKey {
         StorageKey(self.prefix.clone())
     }
+
+    /// Checks that the key is the key of the storage entry.
+    pub fn decode_key(&self, key: &StorageKey) -> Result<(), MetadataError> {
+        let input = strip_prefix(&self.prefix, key)?;
+        ensure_consumed(input)
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -361,6 +654,16 @@ impl<K: Encode> StorageMap<K> {
     }
 }
 
+impl<K: Decode> StorageMap<K> {
+    /// Decodes the map key from a key of the storage entry.
+    pub fn decode_key(&self, key: &StorageKey) -> Result<K, MetadataError> {
+        let mut input = strip_prefix(&self.prefix, key)?;
+        let key = StorageMetadata::unhash_key(&self.hasher, &mut input)?;
+        ensure_consumed(input)?;
+        Ok(key)
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct StorageDoubleMap<K1, K2> {
     _marker: PhantomData<(K1, K2)>,
@@ -378,69 +681,132 @@ impl<K1: Encode, K2: Encode> StorageDoubleMap<K1, K2> {
     }
 }
 
+impl<K1: Decode, K2: Decode> StorageDoubleMap<K1, K2> {
+    /// Decodes both map keys from a key of the storage entry.
+    pub fn decode_key(&self, key: &StorageKey) -> Result<(K1, K2), MetadataError> {
+        let mut input = strip_prefix(&self.prefix, key)?;
+        let key1 = StorageMetadata::unhash_key(&self.hasher1, &mut input)?;
+        let key2 = StorageMetadata::unhash_key(&self.hasher2, &mut input)?;
+        ensure_consumed(input)?;
+        Ok((key1, key2))
+    }
+}
+
+/// Returns the part of the key after the prefix of the storage entry.
+fn strip_prefix<'a>(
+    prefix: &[u8],
+    key: &'a StorageKey,
+) -> Result<&'a [u8], MetadataError> {
+    if key.0.starts_with(prefix) {
+        Ok(&key.0[prefix.len()..])
+    } else {
+        Err(MetadataError::StorageKeyMismatch)
+    }
+}
+
+fn ensure_consumed(input: &[u8]) -> Result<(), MetadataError> {
+    if input.is_empty() {
+        Ok(())
+    } else {
+        Err(MetadataError::StorageKeyDecode(
+            "Unexpected trailing bytes".into(),
+        ))
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct ModuleEventMetadata {
     pub name: String,
     arguments: Vec<EventArg>,
+    type_names: Vec<String>,
+    documentation: Vec<String>,
 }
 
 impl ModuleEventMetadata {
     pub fn arguments(&self) -> Vec<EventArg> {
         self.arguments.to_vec()
     }
+
+    /// Returns the type names of the arguments, as written in the metadata.
+    pub fn type_names(&self) -> &[String] {
+        &self.type_names
+    }
+
+    /// Returns the documentation of the event.
+    pub fn documentation(&self) -> &[String] {
+        &self.documentation
+    }
 }
 
-/// Naive representation of event argument types, supports current set of substrate EventArg types.
+/// Representation of event argument types, parsed from the type names in the metadata.
 /// If and when Substrate uses `type-metadata`, this can be replaced.
 ///
 /// Used to calculate the size of a instance of an event variant without having the concrete type,
 /// so the raw bytes can be extracted from the encoded `Vec<EventRecord<E>>` (without `E` defined).
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
 pub enum EventArg {
+    /// A type which is decoded by a registered type decoder, e.g. `AccountId`.
     Primitive(String),
+    /// A `Vec<T>`.
     Vec(Box<EventArg>),
+    /// A tuple `(A, B, ..)`.
     Tuple(Vec<EventArg>),
+    /// An `Option<T>`.
     Option(Box<EventArg>),
+    /// A fixed size array `[T; N]`.
+    Array(Box<EventArg>, usize),
+    /// A `Compact<T>`.
+    Compact(Box<EventArg>),
+    /// A `BTreeMap<K, V>`.
+    Map(Box<EventArg>, Box<EventArg>),
+    /// Any other generic type, e.g. `Result<T, E>`.
+    Generic(String, Vec<EventArg>),
 }
 
 impl FromStr for EventArg {
     type Err = ConversionError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.starts_with("Vec<") {
-            if s.ends_with('>') {
-                Ok(EventArg::Vec(Box::new(s[4..s.len() - 1].parse()?)))
-            } else {
-                Err(ConversionError::InvalidEventArg(
-                    s.to_string(),
-                    "Expected closing `>` for `Vec`",
-                ))
+        let mut parser = TypeParser::new(s);
+        let arg = parser.parse_type()?;
+        if parser.peek().is_some() {
+            return Err(parser.error("Unexpected trailing characters"))
+        }
+        Ok(arg)
+    }
+}
+
+impl std::fmt::Display for EventArg {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fn write_list(
+            f: &mut std::fmt::Formatter,
+            args: &[EventArg],
+        ) -> std::fmt::Result {
+            for (i, arg) in args.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(", ")?;
+                }
+                write!(f, "{}", arg)?;
             }
-        } else if s.starts_with("Option<") {
-            if s.ends_with('>') {
-                Ok(EventArg::Option(Box::new(s[7..s.len() - 1].parse()?)))
-            } else {
-                Err(ConversionError::InvalidEventArg(
-                    s.to_string(),
-                    "Expected closing `>` for `Option`",
-                ))
+            Ok(())
+        }
+        match self {
+            EventArg::Primitive(name) => f.write_str(name),
+            EventArg::Vec(arg) => write!(f, "Vec<{}>", arg),
+            EventArg::Tuple(args) => {
+                f.write_str("(")?;
+                write_list(f, args)?;
+                f.write_str(")")
             }
-        } else if s.starts_with('(') {
-            if s.ends_with(')') {
-                let mut args = Vec::new();
-                for arg in s[1..s.len() - 1].split(',') {
-                    let arg = arg.trim().parse()?;
-                    args.push(arg)
-                }
-                Ok(EventArg::Tuple(args))
-            } else {
-                Err(ConversionError::InvalidEventArg(
-                    s.to_string(),
-                    "Expecting closing `)` for tuple",
-                ))
+            EventArg::Option(arg) => write!(f, "Option<{}>", arg),
+            EventArg::Array(arg, len) => write!(f, "[{}; {}]", arg, len),
+            EventArg::Compact(arg) => write!(f, "Compact<{}>", arg),
+            EventArg::Map(key, value) => write!(f, "BTreeMap<{}, {}>", key, value),
+            EventArg::Generic(name, args) => {
+                write!(f, "{}<", name)?;
+                write_list(f, args)?;
+                f.write_str(">")
             }
-        } else {
-            Ok(EventArg::Primitive(s.to_string()))
         }
     }
 }
@@ -452,6 +818,14 @@ impl EventArg {
             EventArg::Primitive(p) => vec![p.clone()],
             EventArg::Vec(arg) => arg.primitives(),
             EventArg::Option(arg) => arg.primitives(),
+            EventArg::Array(arg, _) => arg.primitives(),
+            // compact values are decoded without knowing the inner type
+            EventArg::Compact(_) => vec![],
+            EventArg::Map(key, value) => {
+                let mut primitives = key.primitives();
+                primitives.extend(value.primitives());
+                primitives
+            }
             EventArg::Tuple(args) => {
                 let mut primitives = Vec::new();
                 for arg in args {
@@ -459,10 +833,179 @@ impl EventArg {
                 }
                 primitives
             }
+            EventArg::Generic(name, args) => {
+                if is_builtin_generic(name, args.len()) {
+                    let mut primitives = Vec::new();
+                    for arg in args {
+                        primitives.extend(arg.primitives())
+                    }
+                    primitives
+                } else {
+                    vec![self.to_string()]
+                }
+            }
         }
     }
 }
 
+/// Returns `true` for generic types which can be decoded from their type parameters alone.
+pub(crate) fn is_builtin_generic(name: &str, params: usize) -> bool {
+    matches!(
+        (name, params),
+        ("Result", 2) | ("BTreeSet", 1) | ("VecDeque", 1) | ("Box", 1)
+    )
+}
+
+/// Recursive descent parser for the type names found in the metadata.
+struct TypeParser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> TypeParser<'a> {
+    fn new(input: &'a str) -> Self {
+        Self { input, pos: 0 }
+    }
+
+    fn error(&self, msg: &'static str) -> ConversionError {
+        ConversionError::InvalidEventArg(self.input.to_string(), msg)
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.input[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.input[self.pos..].chars().next()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char, msg: &'static str) -> Result<(), ConversionError> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(msg))
+        }
+    }
+
+    fn ident(&mut self) -> Result<&'a str, ConversionError> {
+        self.skip_whitespace();
+        let rest = &self.input[self.pos..];
+        let len = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return Err(self.error("Expected identifier"))
+        }
+        self.pos += len;
+        Ok(&rest[..len])
+    }
+
+    fn eat_path_separator(&mut self) -> bool {
+        self.skip_whitespace();
+        if self.input[self.pos..].starts_with("::") {
+            self.pos += 2;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn parse_type(&mut self) -> Result<EventArg, ConversionError> {
+        match self.peek() {
+            Some('(') => self.parse_tuple(),
+            Some('[') => self.parse_array(),
+            Some(_) => self.parse_path(),
+            None => Err(self.error("Expected type")),
+        }
+    }
+
+    fn parse_list(
+        &mut self,
+        close: char,
+        msg: &'static str,
+    ) -> Result<Vec<EventArg>, ConversionError> {
+        let mut args = Vec::new();
+        while !self.eat(close) {
+            args.push(self.parse_type()?);
+            if !self.eat(',') {
+                self.expect(close, msg)?;
+                break
+            }
+        }
+        Ok(args)
+    }
+
+    fn parse_tuple(&mut self) -> Result<EventArg, ConversionError> {
+        self.expect('(', "Expected `(` for tuple")?;
+        let args = self.parse_list(')', "Expecting closing `)` for tuple")?;
+        Ok(EventArg::Tuple(args))
+    }
+
+    fn parse_array(&mut self) -> Result<EventArg, ConversionError> {
+        self.expect('[', "Expected `[` for array")?;
+        let arg = self.parse_type()?;
+        self.expect(';', "Expected `;` in array")?;
+        let len = self
+            .ident()?
+            .parse()
+            .map_err(|_| self.error("Expected array length"))?;
+        self.expect(']', "Expected closing `]` for array")?;
+        Ok(EventArg::Array(Box::new(arg), len))
+    }
+
+    fn parse_path(&mut self) -> Result<EventArg, ConversionError> {
+        let qualified = self.eat('<');
+        if qualified {
+            // qualified path `<T as Trait>::Name`, only the name is relevant
+            self.parse_type()?;
+            if self.ident()? != "as" {
+                return Err(self.error("Expected `as` in qualified path"))
+            }
+            self.parse_path()?;
+            self.expect('>', "Expected closing `>` for qualified path")?;
+            if !self.eat_path_separator() {
+                return Err(self.error("Expected `::` after qualified path"))
+            }
+        }
+        let mut segments = vec![self.ident()?];
+        while self.eat_path_separator() {
+            segments.push(self.ident()?);
+        }
+        // `T::Name` refers to the associated type `Name`
+        if !qualified && segments.len() > 1 && segments[0] == "T" {
+            segments.remove(0);
+        }
+        let name = segments.join("::");
+        let mut params = if self.eat('<') {
+            self.parse_list('>', "Expected closing `>` for generic")?
+        } else {
+            return Ok(EventArg::Primitive(name))
+        };
+        let arg = match (name.as_str(), params.len()) {
+            ("Vec", 1) => EventArg::Vec(Box::new(params.remove(0))),
+            ("Option", 1) => EventArg::Option(Box::new(params.remove(0))),
+            ("Compact", 1) => EventArg::Compact(Box::new(params.remove(0))),
+            ("BTreeMap", 2) => {
+                let value = params.remove(1);
+                EventArg::Map(Box::new(params.remove(0)), Box::new(value))
+            }
+            _ => EventArg::Generic(name, params),
+        };
+        Ok(arg)
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum ConversionError {
     #[error("Invalid prefix")]
@@ -475,22 +1018,47 @@ pub enum ConversionError {
     InvalidEventArg(String, &'static str),
 }
 
-impl TryFrom<RuntimeMetadataPrefixed> for Metadata {
-    type Error = MetadataError;
-
-    fn try_from(metadata: RuntimeMetadataPrefixed) -> Result<Self, Self::Error> {
-        if metadata.0 != META_RESERVED {
+impl Metadata {
+    /// Decodes prefixed metadata of any supported version, as returned by
+    /// `state_getMetadata`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MetadataError> {
+        let input = &mut &bytes[..];
+        let prefix = u32::decode(input).map_err(MetadataError::Decode)?;
+        if prefix != META_RESERVED {
             return Err(ConversionError::InvalidPrefix.into())
         }
-        let meta = match metadata.1 {
-            RuntimeMetadata::V12(meta) => meta,
-            _ => return Err(ConversionError::InvalidVersion.into()),
-        };
-        let mut modules = HashMap::new();
+        let version = u8::decode(input).map_err(MetadataError::Decode)?;
+        let modules = match version {
+            10 => Self::from_version::<RuntimeMetadataV10>(input),
+            11 => Self::from_version::<RuntimeMetadataV11>(input),
+            12 => Self::from_version::<RuntimeMetadataV12>(input),
+            _ => Err(ConversionError::InvalidVersion.into()),
+        }?;
+        Self::from_modules(modules, bytes.to_vec())
+    }
+
+    /// Returns the prefixed metadata the `Metadata` was decoded from.
+    pub fn encoded(&self) -> &[u8] {
+        &self.encoded
+    }
+
+    fn from_version<M: Decode + IntoModules>(
+        input: &mut &[u8],
+    ) -> Result<Vec<(frame_metadata::ModuleMetadata, ModuleIndices)>, MetadataError> {
+        let metadata = M::decode(input).map_err(MetadataError::Decode)?;
+        Ok(metadata.into_modules()?)
+    }
+
+    /// Converts the modules of the prefixed metadata `encoded`.
+    fn from_modules(
+        modules: Vec<(frame_metadata::ModuleMetadata, ModuleIndices)>,
+        encoded: Vec<u8>,
+    ) -> Result<Self, MetadataError> {
+        let mut modules_map = HashMap::new();
         let mut modules_with_calls = HashMap::new();
         let mut modules_with_events = HashMap::new();
         let mut modules_with_errors = HashMap::new();
-        for module in convert(meta.modules)?.into_iter() {
+        for (module, indices) in modules.into_iter() {
             let module_name = convert(module.name.clone())?;
 
             let mut storage_map = HashMap::new();
@@ -507,25 +1075,31 @@ impl TryFrom<RuntimeMetadataPrefixed> for Metadata {
                     storage_map.insert(storage_prefix, entry);
                 }
             }
-            modules.insert(
+            let mut constant_map = HashMap::new();
+            for constant in convert(module.constants)?.into_iter() {
+                let constant = convert_constant(constant)?;
+                constant_map.insert(constant.name.clone(), constant);
+            }
+            modules_map.insert(
                 module_name.clone(),
                 ModuleMetadata {
-                    index: module.index,
+                    index: indices.module,
                     name: module_name.clone(),
                     storage: storage_map,
+                    constants: constant_map,
                 },
             );
 
             if let Some(calls) = module.calls {
                 let mut call_map = HashMap::new();
                 for (index, call) in convert(calls)?.into_iter().enumerate() {
-                    let name = convert(call.name)?;
-                    call_map.insert(name, index as u8);
+                    let call = convert_call(index as u8, call)?;
+                    call_map.insert(call.name.clone(), call);
                 }
                 modules_with_calls.insert(
                     module_name.clone(),
                     ModuleWithCalls {
-                        index: module.index,
+                        index: indices.calls,
                         calls: call_map,
                     },
                 );
@@ -538,7 +1112,7 @@ impl TryFrom<RuntimeMetadataPrefixed> for Metadata {
                 modules_with_events.insert(
                     module_name.clone(),
                     ModuleWithEvents {
-                        index: module.index,
+                        index: indices.events,
                         name: module_name.clone(),
                         events: event_map,
                     },
@@ -551,21 +1125,139 @@ impl TryFrom<RuntimeMetadataPrefixed> for Metadata {
             modules_with_errors.insert(
                 module_name.clone(),
                 ModuleWithErrors {
-                    index: module.index,
+                    index: indices.module,
                     name: module_name.clone(),
                     errors: error_map,
                 },
             );
         }
         Ok(Metadata {
-            modules,
+            modules: modules_map,
             modules_with_calls,
             modules_with_events,
             modules_with_errors,
+            encoded: Arc::new(encoded),
         })
     }
 }
 
+/// Metadata without modules, which still encodes as valid prefixed metadata.
+impl Default for Metadata {
+    fn default() -> Self {
+        Self::try_from(RuntimeMetadataPrefixed(
+            META_RESERVED,
+            RuntimeMetadata::V12(RuntimeMetadataV12 {
+                modules: DecodeDifferent::Decoded(vec![]),
+                extrinsic: ExtrinsicMetadata {
+                    version: 4,
+                    signed_extensions: vec![],
+                },
+            }),
+        ))
+        .expect("metadata without modules is valid; qed")
+    }
+}
+
+/// Leaves out the encoded metadata, which is long and readable in the modules.
+impl fmt::Debug for Metadata {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Metadata")
+            .field("modules", &self.modules)
+            .field("modules_with_calls", &self.modules_with_calls)
+            .field("modules_with_events", &self.modules_with_events)
+            .field("modules_with_errors", &self.modules_with_errors)
+            .finish()
+    }
+}
+
+/// The indices a runtime uses for a module in calls, events and errors.
+struct ModuleIndices {
+    /// Index of the module, used for errors.
+    module: u8,
+    /// Index of the module in the outer call enum.
+    calls: u8,
+    /// Index of the module in the outer event enum.
+    events: u8,
+}
+
+/// A metadata version which can be converted into `Metadata`.
+///
+/// Supporting a new metadata version means implementing this trait for its decoded
+/// representation and adding it to `Metadata::from_bytes`.
+trait IntoModules {
+    /// Returns the modules with the indices the runtime uses for them.
+    fn into_modules(
+        self,
+    ) -> Result<Vec<(frame_metadata::ModuleMetadata, ModuleIndices)>, ConversionError>;
+}
+
+impl IntoModules for RuntimeMetadataV12 {
+    fn into_modules(
+        self,
+    ) -> Result<Vec<(frame_metadata::ModuleMetadata, ModuleIndices)>, ConversionError>
+    {
+        let modules = convert(self.modules)?
+            .into_iter()
+            .map(|module| {
+                let indices = ModuleIndices {
+                    module: module.index,
+                    calls: module.index,
+                    events: module.index,
+                };
+                (module, indices)
+            })
+            .collect();
+        Ok(modules)
+    }
+}
+
+impl TryFrom<RuntimeMetadataPrefixed> for Metadata {
+    type Error = MetadataError;
+
+    fn try_from(metadata: RuntimeMetadataPrefixed) -> Result<Self, Self::Error> {
+        if metadata.0 != META_RESERVED {
+            return Err(ConversionError::InvalidPrefix.into())
+        }
+        let encoded = metadata.encode();
+        match metadata.1 {
+            RuntimeMetadata::V12(meta) => {
+                Self::from_modules(meta.into_modules()?, encoded)
+            }
+            _ => Err(ConversionError::InvalidVersion.into()),
+        }
+    }
+}
+
+/// Encodes the prefixed metadata as bytes.
+impl Encode for Metadata {
+    fn encode_to<W: Output>(&self, dest: &mut W) {
+        self.encoded.encode_to(dest)
+    }
+}
+
+impl Decode for Metadata {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, CodecError> {
+        let bytes = Vec::<u8>::decode(input)?;
+        Self::from_bytes(&bytes).map_err(|_| "Invalid metadata".into())
+    }
+}
+
+/// Serializes the prefixed metadata as a `0x` prefixed hex string.
+impl Serialize for Metadata {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("0x{}", hex::encode(&*self.encoded)))
+    }
+}
+
+impl<'de> Deserialize<'de> for Metadata {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        let bytes =
+            hex::decode(hex.trim_start_matches("0x")).map_err(D::Error::custom)?;
+        Self::from_bytes(&bytes).map_err(D::Error::custom)
+    }
+}
+
 fn convert<B: 'static, O: 'static>(
     dd: DecodeDifferent<B, O>,
 ) -> Result<O, ConversionError> {
@@ -575,16 +1267,42 @@ fn convert<B: 'static, O: 'static>(
     }
 }
 
+fn convert_call(
+    index: u8,
+    call: frame_metadata::FunctionMetadata,
+) -> Result<CallMetadata, ConversionError> {
+    let name = convert(call.name)?;
+    let mut arguments = Vec::new();
+    for arg in convert(call.arguments)? {
+        arguments.push(CallArgMetadata {
+            name: convert(arg.name)?,
+            ty: convert(arg.ty)?,
+        });
+    }
+    Ok(CallMetadata {
+        index,
+        name,
+        arguments,
+        documentation: convert(call.documentation)?,
+    })
+}
+
 fn convert_event(
     event: frame_metadata::EventMetadata,
 ) -> Result<ModuleEventMetadata, ConversionError> {
     let name = convert(event.name)?;
+    let type_names = convert(event.arguments)?;
     let mut arguments = Vec::new();
-    for arg in convert(event.arguments)? {
+    for arg in &type_names {
         let arg = arg.parse::<EventArg>()?;
         arguments.push(arg);
     }
-    Ok(ModuleEventMetadata { name, arguments })
+    Ok(ModuleEventMetadata {
+        name,
+        arguments,
+        type_names,
+        documentation: convert(event.documentation)?,
+    })
 }
 
 fn convert_entry(
@@ -599,11 +1317,246 @@ fn convert_entry(
         modifier: entry.modifier,
         ty: entry.ty,
         default,
+        documentation: convert(entry.documentation)?,
+    })
+}
+
+fn convert_constant(
+    constant: frame_metadata::ModuleConstantMetadata,
+) -> Result<ModuleConstantMetadata, ConversionError> {
+    Ok(ModuleConstantMetadata {
+        name: convert(constant.name)?,
+        ty: convert(constant.ty)?,
+        value: convert(constant.value)?,
+        documentation: convert(constant.documentation)?,
     })
 }
 
 fn convert_error(
     error: frame_metadata::ErrorMetadata,
-) -> Result<String, ConversionError> {
-    convert(error.name)
+) -> Result<ModuleErrorMetadata, ConversionError> {
+    Ok(ModuleErrorMetadata {
+        name: convert(error.name)?,
+        documentation: convert(error.documentation)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn primitive(name: &str) -> EventArg {
+        EventArg::Primitive(name.to_string())
+    }
+
+    #[test]
+    fn test_parse_event_args() {
+        assert_eq!(
+            "AccountId".parse::<EventArg>().unwrap(),
+            primitive("AccountId")
+        );
+        assert_eq!(
+            "Vec<(AccountId, Balance)>".parse::<EventArg>().unwrap(),
+            EventArg::Vec(Box::new(EventArg::Tuple(vec![
+                primitive("AccountId"),
+                primitive("Balance"),
+            ])))
+        );
+        assert_eq!(
+            "(AccountId, (u32, Option<Hash>))"
+                .parse::<EventArg>()
+                .unwrap(),
+            EventArg::Tuple(vec![
+                primitive("AccountId"),
+                EventArg::Tuple(vec![
+                    primitive("u32"),
+                    EventArg::Option(Box::new(primitive("Hash"))),
+                ]),
+            ])
+        );
+        assert_eq!(
+            "[u8; 32]".parse::<EventArg>().unwrap(),
+            EventArg::Array(Box::new(primitive("u8")), 32)
+        );
+        assert_eq!(
+            "Compact<Balance>".parse::<EventArg>().unwrap(),
+            EventArg::Compact(Box::new(primitive("Balance")))
+        );
+        assert_eq!(
+            "BTreeMap<AccountId, Vec<u8>>".parse::<EventArg>().unwrap(),
+            EventArg::Map(
+                Box::new(primitive("AccountId")),
+                Box::new(EventArg::Vec(Box::new(primitive("u8")))),
+            )
+        );
+        assert_eq!(
+            "Result<(), DispatchError>".parse::<EventArg>().unwrap(),
+            EventArg::Generic(
+                "Result".to_string(),
+                vec![EventArg::Tuple(vec![]), primitive("DispatchError")],
+            )
+        );
+        assert_eq!(
+            "<T as Trait>::Balance".parse::<EventArg>().unwrap(),
+            primitive("Balance")
+        );
+        assert_eq!("T::Hash".parse::<EventArg>().unwrap(), primitive("Hash"));
+    }
+
+    #[test]
+    fn test_parse_invalid_event_args() {
+        assert!("Vec<u8".parse::<EventArg>().is_err());
+        assert!("(u8, u16".parse::<EventArg>().is_err());
+        assert!("[u8; n]".parse::<EventArg>().is_err());
+        assert!("u8>".parse::<EventArg>().is_err());
+    }
+
+    #[test]
+    fn test_display_roundtrip() {
+        for ty in &[
+            "Vec<(AccountId, Balance)>",
+            "[u8; 32]",
+            "BTreeMap<AccountId, Compact<Balance>>",
+            "Result<(), DispatchError>",
+        ] {
+            assert_eq!(&ty.parse::<EventArg>().unwrap().to_string(), ty);
+        }
+    }
+
+    #[test]
+    fn test_decode_legacy_metadata() {
+        // (name, arguments, documentation) of calls and events
+        let item =
+            |name: &str| (name.to_string(), Vec::<String>::new(), Vec::<String>::new());
+        // (name, storage, calls, event, constants, errors) of V11 modules
+        let module = |name: &str, call: Option<&str>, event: Option<&str>| {
+            (
+                name.to_string(),
+                None::<()>,
+                call.map(|call| vec![item(call)]),
+                event.map(|event| vec![item(event)]),
+                Vec::<()>::new(),
+                vec![("Error".to_string(), Vec::<String>::new())],
+            )
+        };
+        let mut bytes = META_RESERVED.encode();
+        bytes.push(11);
+        bytes.extend(
+            vec![
+                module("System", None, Some("ExtrinsicSuccess")),
+                module("Timestamp", Some("set"), None),
+                module("Balances", Some("transfer"), Some("Transfer")),
+            ]
+            .encode(),
+        );
+        // extrinsic version and signed extensions
+        bytes.extend((4u8, Vec::<String>::new()).encode());
+
+        let metadata = Metadata::from_bytes(&bytes).unwrap();
+        assert_eq!(metadata.module("Balances").unwrap().index, 2);
+        assert_eq!(metadata.module_with_calls("Timestamp").unwrap().index, 0);
+        assert_eq!(metadata.module_with_calls("Balances").unwrap().index, 1);
+        assert_eq!(metadata.module_with_events(1).unwrap().name(), "Balances");
+        assert_eq!(metadata.module_with_errors(2).unwrap().name(), "Balances");
+
+        bytes[4] = 9;
+        assert!(Metadata::from_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_default_metadata_roundtrip() {
+        let metadata = Metadata::default();
+        let decoded = Metadata::decode(&mut &metadata.encode()[..]).unwrap();
+        assert_eq!(decoded.encoded(), metadata.encoded());
+        let json = serde_json::to_string(&metadata).unwrap();
+        let deserialized: Metadata = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized.encoded(), metadata.encoded());
+        assert!(!format!("{:?}", metadata).contains("encoded"));
+    }
+
+    #[test]
+    fn test_decode_storage_key() {
+        let entry = |name: &str, ty| {
+            StorageMetadata {
+                module_prefix: "System".to_string(),
+                storage_prefix: name.to_string(),
+                modifier: StorageEntryModifier::Default,
+                ty,
+                default: vec![],
+                documentation: vec![],
+            }
+        };
+        let ty = |ty: &str| DecodeDifferent::Decoded(ty.to_string());
+        let account = entry(
+            "Account",
+            StorageEntryType::Map {
+                hasher: StorageHasher::Blake2_128Concat,
+                key: ty("T::AccountId"),
+                value: ty("AccountInfo"),
+                unused: false,
+            },
+        );
+        let hashed = entry(
+            "BlockHash",
+            StorageEntryType::Map {
+                hasher: StorageHasher::Blake2_256,
+                key: ty("T::BlockNumber"),
+                value: ty("T::Hash"),
+                unused: false,
+            },
+        );
+        let approvals = entry(
+            "Approvals",
+            StorageEntryType::DoubleMap {
+                hasher: StorageHasher::Twox64Concat,
+                key1: ty("u32"),
+                key2: ty("T::AccountId"),
+                value: ty("bool"),
+                key2_hasher: StorageHasher::Identity,
+            },
+        );
+        let mut metadata = Metadata::default();
+        metadata.modules.insert(
+            "System".to_string(),
+            ModuleMetadata {
+                index: 0,
+                name: "System".to_string(),
+                storage: vec![account, hashed, approvals]
+                    .into_iter()
+                    .map(|entry| (entry.storage_prefix.clone(), entry))
+                    .collect(),
+                constants: HashMap::new(),
+            },
+        );
+        let system = metadata.module("System").unwrap();
+
+        let account = system.storage("Account").unwrap();
+        let key = account.map().unwrap().key(&[7u8; 32]);
+        let (module, entry) = metadata.storage_for_key(&key).unwrap();
+        assert_eq!((module.name(), entry.name()), ("System", "Account"));
+        assert_eq!(
+            account.map::<[u8; 32]>().unwrap().decode_key(&key).unwrap(),
+            [7u8; 32]
+        );
+        assert!(account.map::<u64>().unwrap().decode_key(&key).is_err());
+
+        let approvals = system.storage("Approvals").unwrap().double_map().unwrap();
+        let key = approvals.key(&5u32, &[1u8; 32]);
+        assert_eq!(approvals.decode_key(&key).unwrap(), (5u32, [1u8; 32]));
+
+        let hashed = system.storage("BlockHash").unwrap();
+        let key = hashed.map().unwrap().key(&1u32);
+        assert!(matches!(
+            hashed.map::<u32>().unwrap().decode_key(&key),
+            Err(MetadataError::OpaqueStorageKey(StorageHasher::Blake2_256))
+        ));
+        assert!(matches!(
+            account.map::<u32>().unwrap().decode_key(&key),
+            Err(MetadataError::StorageKeyMismatch)
+        ));
+        assert!(matches!(
+            metadata.storage_for_key(&StorageKey(vec![0; 32])),
+            Err(MetadataError::StorageKeyNotFound)
+        ));
+    }
 }
@@ -0,0 +1,382 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of substrate-subxt.
+//
+// subxt is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// subxt is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-subxt.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Checks of compiled `Call`, `Store` and `Event` types against the metadata.
+
+use frame_metadata::StorageEntryType;
+use std::fmt;
+
+use super::{
+    diff::type_name,
+    EventArg,
+    Metadata,
+    MetadataItem,
+};
+
+/// A mismatch between a compiled type and the metadata.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Incompatibility {
+    /// The item is not in the metadata.
+    Missing(MetadataItem),
+    /// The number of arguments or storage keys differs.
+    ArgumentCount {
+        /// The item.
+        item: MetadataItem,
+        /// The number in the metadata.
+        metadata: usize,
+        /// The number of the compiled type.
+        compiled: usize,
+    },
+    /// The type of an argument or a storage key differs.
+    ArgumentType {
+        /// The item.
+        item: MetadataItem,
+        /// The position of the argument.
+        index: usize,
+        /// The type in the metadata.
+        metadata: String,
+        /// The type of the compiled type.
+        compiled: String,
+    },
+    /// The type of a storage value differs.
+    ReturnType {
+        /// The item.
+        item: MetadataItem,
+        /// The type in the metadata.
+        metadata: String,
+        /// The type of the compiled type.
+        compiled: String,
+    },
+}
+
+impl fmt::Display for Incompatibility {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Missing(item) => write!(f, "{} not found", item),
+            Self::ArgumentCount {
+                item,
+                metadata,
+                compiled,
+            } => {
+                write!(
+                    f,
+                    "{} has {} arguments, compiled with {}",
+                    item, metadata, compiled
+                )
+            }
+            Self::ArgumentType {
+                item,
+                index,
+                metadata,
+                compiled,
+            } => {
+                write!(
+                    f,
+                    "argument {} of {} has type {}, compiled with {}",
+                    index, item, metadata, compiled
+                )
+            }
+            Self::ReturnType {
+                item,
+                metadata,
+                compiled,
+            } => {
+                write!(
+                    f,
+                    "{} has type {}, compiled with {}",
+                    item, metadata, compiled
+                )
+            }
+        }
+    }
+}
+
+impl Metadata {
+    /// Compares the argument types of a compiled call with the metadata.
+    ///
+    /// Only checks that the call exists if no argument types are given.
+    pub fn check_call(
+        &self,
+        module: &str,
+        function: &str,
+        arg_types: Option<&[&str]>,
+    ) -> Vec<Incompatibility> {
+        let item = || MetadataItem::Call(module.to_string(), function.to_string());
+        let call = self
+            .modules_with_calls
+            .get(module)
+            .and_then(|module| module.calls.get(function));
+        match (call, arg_types) {
+            (Some(call), Some(arg_types)) => {
+                let expected = call
+                    .arguments()
+                    .iter()
+                    .map(|arg| arg.ty().to_string())
+                    .collect::<Vec<_>>();
+                check_args(item, &expected, arg_types)
+            }
+            (Some(_), None) => vec![],
+            (None, _) => vec![Incompatibility::Missing(item())],
+        }
+    }
+
+    /// Compares the key and value types of a compiled storage entry with the metadata.
+    ///
+    /// Only checks that the storage entry exists if no key or value types are given.
+    pub fn check_store(
+        &self,
+        module: &str,
+        field: &str,
+        key_types: Option<&[&str]>,
+        return_type: Option<&str>,
+    ) -> Vec<Incompatibility> {
+        let item = || MetadataItem::Storage(module.to_string(), field.to_string());
+        let storage = match self
+            .modules
+            .get(module)
+            .and_then(|module| module.storage.get(field))
+        {
+            Some(storage) => storage,
+            None => return vec![Incompatibility::Missing(item())],
+        };
+        let (keys, value) = match &storage.ty {
+            StorageEntryType::Plain(value) => (vec![], value),
+            StorageEntryType::Map { key, value, .. } => (vec![type_name(key)], value),
+            StorageEntryType::DoubleMap {
+                key1, key2, value, ..
+            } => (vec![type_name(key1), type_name(key2)], value),
+        };
+        let mut incompatibilities = match key_types {
+            Some(key_types) => check_args(item, &keys, key_types),
+            None => vec![],
+        };
+        let value = type_name(value);
+        match return_type {
+            Some(return_type) if !is_compatible(&value, return_type) => {
+                incompatibilities.push(Incompatibility::ReturnType {
+                    item: item(),
+                    metadata: value,
+                    compiled: return_type.to_string(),
+                });
+            }
+            _ => {}
+        }
+        incompatibilities
+    }
+
+    /// Compares the argument types of a compiled event with the metadata.
+    ///
+    /// Only checks that the event exists if no argument types are given.
+    pub fn check_event(
+        &self,
+        module: &str,
+        event: &str,
+        arg_types: Option<&[&str]>,
+    ) -> Vec<Incompatibility> {
+        let item = || MetadataItem::Event(module.to_string(), event.to_string());
+        match (self.event(module, event), arg_types) {
+            (Ok(event), Some(arg_types)) => {
+                let expected = event
+                    .arguments
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>();
+                check_args(item, &expected, arg_types)
+            }
+            (Ok(_), None) => vec![],
+            (Err(_), _) => vec![Incompatibility::Missing(item())],
+        }
+    }
+}
+
+fn check_args(
+    item: impl Fn() -> MetadataItem,
+    expected: &[String],
+    compiled: &[&str],
+) -> Vec<Incompatibility> {
+    if expected.len() != compiled.len() {
+        return vec![Incompatibility::ArgumentCount {
+            item: item(),
+            metadata: expected.len(),
+            compiled: compiled.len(),
+        }]
+    }
+    expected
+        .iter()
+        .zip(compiled)
+        .enumerate()
+        .filter(|(_, (expected, compiled))| !is_compatible(expected, compiled))
+        .map(|(index, (expected, compiled))| {
+            Incompatibility::ArgumentType {
+                item: item(),
+                index,
+                metadata: expected.to_string(),
+                compiled: compiled.to_string(),
+            }
+        })
+        .collect()
+}
+
+/// Returns `true` if a type name of the metadata and of a compiled type refer to the
+/// same type.
+fn is_compatible(metadata: &str, compiled: &str) -> bool {
+    match (metadata.parse::<EventArg>(), compiled.parse::<EventArg>()) {
+        (Ok(metadata), Ok(compiled)) => {
+            matches_type(&canonical(metadata), &canonical(compiled))
+        }
+        _ => {
+            let strip = |ty: &str| ty.split_whitespace().collect::<String>();
+            strip(metadata) == strip(compiled)
+        }
+    }
+}
+
+fn matches_type(metadata: &EventArg, compiled: &EventArg) -> bool {
+    let all = |metadata: &[EventArg], compiled: &[EventArg]| {
+        metadata.len() == compiled.len()
+            && metadata
+                .iter()
+                .zip(compiled)
+                .all(|(metadata, compiled)| matches_type(metadata, compiled))
+    };
+    match (metadata, compiled) {
+        (EventArg::Primitive(metadata), EventArg::Primitive(compiled)) => {
+            metadata == compiled
+        }
+        (EventArg::Vec(metadata), EventArg::Vec(compiled))
+        | (EventArg::Option(metadata), EventArg::Option(compiled))
+        | (EventArg::Compact(metadata), EventArg::Compact(compiled)) => {
+            matches_type(metadata, compiled)
+        }
+        (EventArg::Array(metadata, m), EventArg::Array(compiled, c)) => {
+            m == c && matches_type(metadata, compiled)
+        }
+        (EventArg::Tuple(metadata), EventArg::Tuple(compiled)) => all(metadata, compiled),
+        (EventArg::Map(mk, mv), EventArg::Map(ck, cv)) => {
+            matches_type(mk, ck) && matches_type(mv, cv)
+        }
+        (EventArg::Generic(metadata, margs), EventArg::Generic(compiled, cargs)) => {
+            // structs generic over the runtime, e.g. `AccountInfo<T>`, stand for the
+            // several parameters the runtime fills in, a single parameter is compared
+            let runtime_generic = margs.len() > 1
+                && cargs.len() == 1
+                && cargs[0] == EventArg::Primitive("T".to_string());
+            metadata == compiled && (runtime_generic || all(margs, cargs))
+        }
+        _ => false,
+    }
+}
+
+/// Normalizes the names of types which are known under different names.
+fn canonical(arg: EventArg) -> EventArg {
+    let canonical_box = |arg: Box<EventArg>| Box::new(canonical(*arg));
+    match arg {
+        EventArg::Primitive(name) => {
+            match name.rsplit("::").next().unwrap_or_default() {
+                "Bytes" => EventArg::Vec(Box::new(EventArg::Primitive("u8".into()))),
+                "Source" | "LookupSource" => EventArg::Primitive("Address".into()),
+                "BalanceOf" => EventArg::Primitive("Balance".into()),
+                name => EventArg::Primitive(name.to_string()),
+            }
+        }
+        EventArg::Vec(arg) => EventArg::Vec(canonical_box(arg)),
+        EventArg::Option(arg) => EventArg::Option(canonical_box(arg)),
+        EventArg::Compact(arg) => EventArg::Compact(canonical_box(arg)),
+        EventArg::Array(arg, len) => EventArg::Array(canonical_box(arg), len),
+        EventArg::Tuple(args) => {
+            EventArg::Tuple(args.into_iter().map(canonical).collect())
+        }
+        EventArg::Map(key, value) => {
+            EventArg::Map(canonical_box(key), canonical_box(value))
+        }
+        EventArg::Generic(name, mut args) if name == "Box" && args.len() == 1 => {
+            canonical(args.remove(0))
+        }
+        // aliases of associated types, e.g. `BalanceOf<T>`
+        EventArg::Generic(name, args)
+            if name.ends_with("Of") && args == [EventArg::Primitive("T".to_string())] =>
+        {
+            EventArg::Primitive(name.trim_end_matches("Of").to_string())
+        }
+        EventArg::Generic(name, args) => {
+            EventArg::Generic(
+                name.rsplit("::").next().unwrap_or_default().to_string(),
+                args.into_iter().map(canonical).collect(),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_compatible() {
+        assert!(is_compatible(
+            "<T::Lookup as StaticLookup>::Source",
+            "< T as System > :: Address"
+        ));
+        assert!(is_compatible(
+            "Compact<T::Balance>",
+            "Compact<T :: Balance>"
+        ));
+        assert!(is_compatible(
+            "Compact<BalanceOf<T>>",
+            "Compact<T :: Balance>"
+        ));
+        assert!(is_compatible("Vec<u8>", "Bytes"));
+        assert!(is_compatible(
+            "AccountInfo<T::Index, T::AccountData>",
+            "AccountInfo < T >"
+        ));
+        assert!(!is_compatible("RefCount<u32>", "RefCount < T >"));
+        assert!(!is_compatible("Compact<T::Balance>", "T :: Balance"));
+        assert!(!is_compatible("Vec<T::AccountId>", "Vec<u8>"));
+    }
+
+    #[test]
+    fn test_check_call() {
+        let metadata = Metadata::default();
+        assert_eq!(
+            metadata.check_call("Balances", "transfer", None),
+            vec![Incompatibility::Missing(MetadataItem::Call(
+                "Balances".into(),
+                "transfer".into()
+            ))]
+        );
+        let expected = vec!["Source".to_string(), "Compact<Balance>".to_string()];
+        let item = || MetadataItem::Call("Balances".into(), "transfer".into());
+        assert!(check_args(item, &expected, &["Address", "Compact<Balance>"]).is_empty());
+        assert_eq!(
+            check_args(item, &expected, &["Address", "Balance"]),
+            vec![Incompatibility::ArgumentType {
+                item: item(),
+                index: 1,
+                metadata: "Compact<Balance>".into(),
+                compiled: "Balance".into(),
+            }]
+        );
+        assert_eq!(
+            check_args(item, &expected, &["Address"]),
+            vec![Incompatibility::ArgumentCount {
+                item: item(),
+                metadata: 2,
+                compiled: 1,
+            }]
+        );
+    }
+}
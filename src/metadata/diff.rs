@@ -0,0 +1,690 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of substrate-subxt.
+//
+// subxt is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// subxt is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-subxt.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Structural comparison of two versions of the metadata.
+
+use frame_metadata::{
+    DecodeDifferent,
+    StorageEntryType,
+};
+use std::{
+    collections::BTreeMap,
+    fmt,
+};
+
+use super::{
+    Metadata,
+    ModuleMetadata,
+    ModuleWithCalls,
+    ModuleWithErrors,
+    ModuleWithEvents,
+    StorageMetadata,
+};
+
+/// An item of the metadata.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum MetadataItem {
+    /// A module.
+    Module(String),
+    /// The variant of a module in the outer call enum.
+    ModuleCalls(String),
+    /// The variant of a module in the outer event enum.
+    ModuleEvents(String),
+    /// A storage entry of a module.
+    Storage(String, String),
+    /// A call of a module.
+    Call(String, String),
+    /// An event of a module.
+    Event(String, String),
+    /// An error of a module.
+    Error(String, String),
+    /// A constant of a module.
+    Constant(String, String),
+}
+
+impl fmt::Display for MetadataItem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Module(module) => write!(f, "module {}", module),
+            Self::ModuleCalls(module) => write!(f, "calls of module {}", module),
+            Self::ModuleEvents(module) => write!(f, "events of module {}", module),
+            Self::Storage(module, name) => write!(f, "storage {}::{}", module, name),
+            Self::Call(module, name) => write!(f, "call {}::{}", module, name),
+            Self::Event(module, name) => write!(f, "event {}::{}", module, name),
+            Self::Error(module, name) => write!(f, "error {}::{}", module, name),
+            Self::Constant(module, name) => write!(f, "constant {}::{}", module, name),
+        }
+    }
+}
+
+/// A change between two versions of the metadata.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum MetadataChange {
+    /// The item only exists in the new metadata.
+    Added(MetadataItem),
+    /// The item only exists in the old metadata.
+    Removed(MetadataItem),
+    /// The item was renamed, keeping its index or type.
+    Renamed {
+        /// The item under its old name.
+        item: MetadataItem,
+        /// The new name.
+        to: String,
+    },
+    /// The index of the item changed, breaking the encoding of calls or the decoding of
+    /// events and errors.
+    IndexChanged {
+        /// The item.
+        item: MetadataItem,
+        /// The old index.
+        from: u8,
+        /// The new index.
+        to: u8,
+    },
+    /// The argument types of a call or an event changed.
+    ArgumentsChanged {
+        /// The item.
+        item: MetadataItem,
+        /// The old argument types.
+        from: Vec<String>,
+        /// The new argument types.
+        to: Vec<String>,
+    },
+    /// The type of a storage entry or a constant changed.
+    TypeChanged {
+        /// The item.
+        item: MetadataItem,
+        /// The old type.
+        from: String,
+        /// The new type.
+        to: String,
+    },
+    /// The key hashers of a storage entry changed.
+    HashersChanged {
+        /// The item.
+        item: MetadataItem,
+        /// The old hashers.
+        from: Vec<String>,
+        /// The new hashers.
+        to: Vec<String>,
+    },
+    /// The modifier of a storage entry changed.
+    ModifierChanged {
+        /// The item.
+        item: MetadataItem,
+        /// The old modifier.
+        from: String,
+        /// The new modifier.
+        to: String,
+    },
+}
+
+impl fmt::Display for MetadataChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Added(item) => write!(f, "added {}", item),
+            Self::Removed(item) => write!(f, "removed {}", item),
+            Self::Renamed { item, to } => write!(f, "renamed {} to {}", item, to),
+            Self::IndexChanged { item, from, to } => {
+                write!(f, "changed index of {} from {} to {}", item, from, to)
+            }
+            Self::ArgumentsChanged { item, from, to } => {
+                write!(
+                    f,
+                    "changed arguments of {} from ({}) to ({})",
+                    item,
+                    from.join(", "),
+                    to.join(", ")
+                )
+            }
+            Self::TypeChanged { item, from, to } => {
+                write!(f, "changed type of {} from {} to {}", item, from, to)
+            }
+            Self::HashersChanged { item, from, to } => {
+                write!(
+                    f,
+                    "changed hashers of {} from [{}] to [{}]",
+                    item,
+                    from.join(", "),
+                    to.join(", ")
+                )
+            }
+            Self::ModifierChanged { item, from, to } => {
+                write!(f, "changed modifier of {} from {} to {}", item, from, to)
+            }
+        }
+    }
+}
+
+/// The changes between two versions of the metadata, ordered by module.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct MetadataDiff {
+    changes: Vec<MetadataChange>,
+}
+
+impl MetadataDiff {
+    /// Returns the changes.
+    pub fn changes(&self) -> &[MetadataChange] {
+        &self.changes
+    }
+
+    /// Returns `true` if the metadata did not change.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+impl fmt::Display for MetadataDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for change in &self.changes {
+            writeln!(f, "{}", change)?;
+        }
+        Ok(())
+    }
+}
+
+impl Metadata {
+    /// Compares the metadata with a newer version of it.
+    ///
+    /// Items which were removed and added with the same index, or the same type for
+    /// storage entries and constants, are reported as renamed.
+    pub fn diff(&self, other: &Metadata) -> MetadataDiff {
+        let mut changes = Vec::new();
+        let old = sorted(
+            self.modules
+                .iter()
+                .map(|(name, module)| (name.as_str(), module)),
+        );
+        let new = sorted(
+            other
+                .modules
+                .iter()
+                .map(|(name, module)| (name.as_str(), module)),
+        );
+        diff_items(
+            &old,
+            &new,
+            |module| MetadataItem::Module(module.to_string()),
+            |old, new| old.index == new.index,
+            |name, old_module, new_module, changes| {
+                diff_modules(self, other, name, old_module, new_module, changes)
+            },
+            &mut changes,
+        );
+        MetadataDiff { changes }
+    }
+}
+
+fn sorted<'a, V>(items: impl Iterator<Item = (&'a str, V)>) -> BTreeMap<&'a str, V> {
+    items.collect()
+}
+
+/// Reports added, removed and renamed items and compares the items in both versions.
+///
+/// `compare` is called with the name of the item in the new metadata.
+fn diff_items<'a, V>(
+    old: &BTreeMap<&'a str, V>,
+    new: &BTreeMap<&'a str, V>,
+    item: impl Fn(&str) -> MetadataItem,
+    is_rename: impl Fn(&V, &V) -> bool,
+    mut compare: impl FnMut(&str, &V, &V, &mut Vec<MetadataChange>),
+    changes: &mut Vec<MetadataChange>,
+) {
+    let mut added = new
+        .keys()
+        .copied()
+        .filter(|name| !old.contains_key(name))
+        .collect::<Vec<_>>();
+    for (name, old_value) in old {
+        if let Some(new_value) = new.get(name) {
+            compare(name, old_value, new_value, changes);
+        } else if let Some(pos) = added
+            .iter()
+            .position(|added| is_rename(old_value, &new[added]))
+        {
+            let to = added.remove(pos);
+            changes.push(MetadataChange::Renamed {
+                item: item(name),
+                to: to.to_string(),
+            });
+            compare(to, old_value, &new[to], changes);
+        } else {
+            changes.push(MetadataChange::Removed(item(name)));
+        }
+    }
+    for name in added {
+        changes.push(MetadataChange::Added(item(name)));
+    }
+}
+
+fn diff_modules(
+    old_metadata: &Metadata,
+    new_metadata: &Metadata,
+    module: &str,
+    old: &ModuleMetadata,
+    new: &ModuleMetadata,
+    changes: &mut Vec<MetadataChange>,
+) {
+    if old.index != new.index {
+        changes.push(MetadataChange::IndexChanged {
+            item: MetadataItem::Module(module.to_string()),
+            from: old.index,
+            to: new.index,
+        });
+    }
+
+    // storage
+    diff_items(
+        &sorted(old.storage.iter().map(|(name, s)| (name.as_str(), s))),
+        &sorted(new.storage.iter().map(|(name, s)| (name.as_str(), s))),
+        |name| MetadataItem::Storage(module.to_string(), name.to_string()),
+        |old, new| storage_signature(old) == storage_signature(new),
+        |name, old, new, changes| {
+            let item = || MetadataItem::Storage(module.to_string(), name.to_string());
+            let (old_ty, old_hashers, old_modifier) = storage_signature(old);
+            let (new_ty, new_hashers, new_modifier) = storage_signature(new);
+            if old_ty != new_ty {
+                changes.push(MetadataChange::TypeChanged {
+                    item: item(),
+                    from: old_ty,
+                    to: new_ty,
+                });
+            }
+            if old_hashers != new_hashers {
+                changes.push(MetadataChange::HashersChanged {
+                    item: item(),
+                    from: old_hashers,
+                    to: new_hashers,
+                });
+            }
+            if old_modifier != new_modifier {
+                changes.push(MetadataChange::ModifierChanged {
+                    item: item(),
+                    from: old_modifier,
+                    to: new_modifier,
+                });
+            }
+        },
+        changes,
+    );
+
+    // calls
+    let old_calls = old_metadata.modules_with_calls.get(&old.name);
+    let new_calls = new_metadata.modules_with_calls.get(&new.name);
+    if let (Some(old_calls), Some(new_calls)) = (old_calls, new_calls) {
+        if old_calls.index != new_calls.index
+            && (old_calls.index, new_calls.index) != (old.index, new.index)
+        {
+            changes.push(MetadataChange::IndexChanged {
+                item: MetadataItem::ModuleCalls(module.to_string()),
+                from: old_calls.index,
+                to: new_calls.index,
+            });
+        }
+    }
+    diff_items(
+        &call_signatures(old_calls),
+        &call_signatures(new_calls),
+        |name| MetadataItem::Call(module.to_string(), name.to_string()),
+        |old, new| old == new,
+        |name, old, new, changes| {
+            diff_signature(
+                MetadataItem::Call(module.to_string(), name.to_string()),
+                old,
+                new,
+                changes,
+            )
+        },
+        changes,
+    );
+
+    // events
+    let old_events = old_metadata.modules_with_events.get(&old.name);
+    let new_events = new_metadata.modules_with_events.get(&new.name);
+    if let (Some(old_events), Some(new_events)) = (old_events, new_events) {
+        if old_events.index != new_events.index
+            && (old_events.index, new_events.index) != (old.index, new.index)
+        {
+            changes.push(MetadataChange::IndexChanged {
+                item: MetadataItem::ModuleEvents(module.to_string()),
+                from: old_events.index,
+                to: new_events.index,
+            });
+        }
+    }
+    diff_items(
+        &event_signatures(old_events),
+        &event_signatures(new_events),
+        |name| MetadataItem::Event(module.to_string(), name.to_string()),
+        |old, new| old == new,
+        |name, old, new, changes| {
+            diff_signature(
+                MetadataItem::Event(module.to_string(), name.to_string()),
+                old,
+                new,
+                changes,
+            )
+        },
+        changes,
+    );
+
+    // errors
+    diff_items(
+        &error_indices(old_metadata.modules_with_errors.get(&old.name)),
+        &error_indices(new_metadata.modules_with_errors.get(&new.name)),
+        |name| MetadataItem::Error(module.to_string(), name.to_string()),
+        |old, new| old == new,
+        |name, old, new, changes| {
+            if old != new {
+                changes.push(MetadataChange::IndexChanged {
+                    item: MetadataItem::Error(module.to_string(), name.to_string()),
+                    from: *old,
+                    to: *new,
+                });
+            }
+        },
+        changes,
+    );
+
+    // constants
+    diff_items(
+        &sorted(old.constants.iter().map(|(name, c)| (name.as_str(), c))),
+        &sorted(new.constants.iter().map(|(name, c)| (name.as_str(), c))),
+        |name| MetadataItem::Constant(module.to_string(), name.to_string()),
+        |old, new| old.ty == new.ty && old.value == new.value,
+        |name, old, new, changes| {
+            if old.ty != new.ty {
+                changes.push(MetadataChange::TypeChanged {
+                    item: MetadataItem::Constant(module.to_string(), name.to_string()),
+                    from: old.ty.clone(),
+                    to: new.ty.clone(),
+                });
+            }
+        },
+        changes,
+    );
+}
+
+/// Returns the index and argument types of the calls of a module.
+fn call_signatures(
+    module: Option<&ModuleWithCalls>,
+) -> BTreeMap<&str, (u8, Vec<String>)> {
+    sorted(module.into_iter().flat_map(|module| {
+        module.calls().map(|call| {
+            let args = call
+                .arguments()
+                .iter()
+                .map(|arg| arg.ty().to_string())
+                .collect();
+            (call.name(), (call.index(), args))
+        })
+    }))
+}
+
+/// Returns the index and argument types of the events of a module.
+fn event_signatures(
+    module: Option<&ModuleWithEvents>,
+) -> BTreeMap<&str, (u8, Vec<String>)> {
+    sorted(module.into_iter().flat_map(|module| {
+        module.events.iter().map(|(index, event)| {
+            let args = event.arguments.iter().map(ToString::to_string).collect();
+            (event.name.as_str(), (*index, args))
+        })
+    }))
+}
+
+/// Returns the indices of the errors of a module.
+fn error_indices(module: Option<&ModuleWithErrors>) -> BTreeMap<&str, u8> {
+    sorted(module.into_iter().flat_map(|module| {
+        module
+            .errors
+            .iter()
+            .map(|(index, error)| (error.name.as_str(), *index))
+    }))
+}
+
+/// Compares the index and argument types of a call or an event.
+fn diff_signature(
+    item: MetadataItem,
+    old: &(u8, Vec<String>),
+    new: &(u8, Vec<String>),
+    changes: &mut Vec<MetadataChange>,
+) {
+    if old.0 != new.0 {
+        changes.push(MetadataChange::IndexChanged {
+            item: item.clone(),
+            from: old.0,
+            to: new.0,
+        });
+    }
+    if old.1 != new.1 {
+        changes.push(MetadataChange::ArgumentsChanged {
+            item,
+            from: old.1.clone(),
+            to: new.1.clone(),
+        });
+    }
+}
+
+/// Returns the type, the key hashers and the modifier of a storage entry.
+fn storage_signature(storage: &StorageMetadata) -> (String, Vec<String>, String) {
+    let (ty, hashers) = match &storage.ty {
+        StorageEntryType::Plain(value) => (type_name(value), vec![]),
+        StorageEntryType::Map {
+            hasher, key, value, ..
+        } => {
+            (
+                format!("Map<{}, {}>", type_name(key), type_name(value)),
+                vec![format!("{:?}", hasher)],
+            )
+        }
+        StorageEntryType::DoubleMap {
+            hasher,
+            key1,
+            key2,
+            value,
+            key2_hasher,
+        } => {
+            (
+                format!(
+                    "DoubleMap<{}, {}, {}>",
+                    type_name(key1),
+                    type_name(key2),
+                    type_name(value)
+                ),
+                vec![format!("{:?}", hasher), format!("{:?}", key2_hasher)],
+            )
+        }
+    };
+    (ty, hashers, format!("{:?}", storage.modifier))
+}
+
+pub(super) fn type_name<O: AsRef<str>>(ty: &DecodeDifferent<&'static str, O>) -> String {
+    match ty {
+        DecodeDifferent::Encode(name) => name.to_string(),
+        DecodeDifferent::Decoded(name) => name.as_ref().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::{
+        CallArgMetadata,
+        CallMetadata,
+        ModuleEventMetadata,
+    };
+    use frame_metadata::{
+        StorageEntryModifier,
+        StorageHasher,
+    };
+    use std::collections::HashMap;
+
+    fn add_module(
+        metadata: &mut Metadata,
+        name: &str,
+        index: u8,
+        storage: Vec<(&str, StorageHasher)>,
+        calls: Vec<(&str, Vec<&str>)>,
+        events: Vec<(&str, Vec<&str>)>,
+    ) {
+        let storage = storage
+            .into_iter()
+            .map(|(entry, hasher)| {
+                let meta = StorageMetadata {
+                    module_prefix: name.to_string(),
+                    storage_prefix: entry.to_string(),
+                    modifier: StorageEntryModifier::Default,
+                    ty: StorageEntryType::Map {
+                        hasher,
+                        key: DecodeDifferent::Decoded("AccountId".to_string()),
+                        value: DecodeDifferent::Decoded("Balance".to_string()),
+                        unused: false,
+                    },
+                    default: vec![],
+                    documentation: vec![],
+                };
+                (entry.to_string(), meta)
+            })
+            .collect();
+        metadata.modules.insert(
+            name.to_string(),
+            ModuleMetadata {
+                index,
+                name: name.to_string(),
+                storage,
+                constants: HashMap::new(),
+            },
+        );
+        let calls = calls
+            .into_iter()
+            .enumerate()
+            .map(|(index, (call, args))| {
+                let arguments = args
+                    .into_iter()
+                    .map(|ty| {
+                        CallArgMetadata {
+                            name: "arg".to_string(),
+                            ty: ty.to_string(),
+                        }
+                    })
+                    .collect();
+                let meta = CallMetadata {
+                    index: index as u8,
+                    name: call.to_string(),
+                    arguments,
+                    documentation: vec![],
+                };
+                (call.to_string(), meta)
+            })
+            .collect();
+        metadata
+            .modules_with_calls
+            .insert(name.to_string(), ModuleWithCalls { index, calls });
+        let events = events
+            .into_iter()
+            .enumerate()
+            .map(|(index, (event, args))| {
+                let meta = ModuleEventMetadata {
+                    name: event.to_string(),
+                    arguments: args.iter().map(|ty| ty.parse().unwrap()).collect(),
+                    type_names: args.iter().map(|ty| ty.to_string()).collect(),
+                    documentation: vec![],
+                };
+                (index as u8, meta)
+            })
+            .collect();
+        metadata.modules_with_events.insert(
+            name.to_string(),
+            ModuleWithEvents {
+                index,
+                name: name.to_string(),
+                events,
+            },
+        );
+        metadata.modules_with_errors.insert(
+            name.to_string(),
+            ModuleWithErrors {
+                index,
+                name: name.to_string(),
+                errors: HashMap::new(),
+            },
+        );
+    }
+
+    #[test]
+    fn test_metadata_diff() {
+        let mut old = Metadata::default();
+        add_module(&mut old, "System", 0, vec![], vec![], vec![]);
+        add_module(
+            &mut old,
+            "Balances",
+            1,
+            vec![("Account", StorageHasher::Blake2_128Concat)],
+            vec![
+                ("transfer", vec!["Source", "Compact<Balance>"]),
+                ("set_balance", vec!["Source"]),
+            ],
+            vec![("Transfer", vec!["AccountId", "AccountId", "Balance"])],
+        );
+        add_module(&mut old, "Sudo", 2, vec![], vec![], vec![]);
+        assert!(old.diff(&old).is_empty());
+
+        let mut new = Metadata::default();
+        add_module(&mut new, "System", 0, vec![], vec![], vec![]);
+        add_module(
+            &mut new,
+            "Balances",
+            2,
+            vec![("Accounts", StorageHasher::Blake2_128Concat)],
+            vec![
+                ("transfer", vec!["Source", "Compact<Balance>", "bool"]),
+                ("force_balance", vec!["Source"]),
+            ],
+            vec![("Transfer", vec!["AccountId", "AccountId", "Balance"])],
+        );
+        add_module(&mut new, "Root", 3, vec![], vec![], vec![]);
+        add_module(&mut new, "Identity", 1, vec![], vec![], vec![]);
+
+        let balances = || "Balances".to_string();
+        assert_eq!(
+            old.diff(&new).changes(),
+            &[
+                MetadataChange::IndexChanged {
+                    item: MetadataItem::Module(balances()),
+                    from: 1,
+                    to: 2,
+                },
+                MetadataChange::Renamed {
+                    item: MetadataItem::Storage(balances(), "Account".into()),
+                    to: "Accounts".to_string(),
+                },
+                MetadataChange::Renamed {
+                    item: MetadataItem::Call(balances(), "set_balance".into()),
+                    to: "force_balance".to_string(),
+                },
+                MetadataChange::ArgumentsChanged {
+                    item: MetadataItem::Call(balances(), "transfer".into()),
+                    from: vec!["Source".into(), "Compact<Balance>".into()],
+                    to: vec!["Source".into(), "Compact<Balance>".into(), "bool".into()],
+                },
+                MetadataChange::Removed(MetadataItem::Module("Sudo".to_string())),
+                MetadataChange::Added(MetadataItem::Module("Identity".to_string())),
+                MetadataChange::Added(MetadataItem::Module("Root".to_string())),
+            ][..]
+        );
+    }
+}
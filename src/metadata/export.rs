@@ -0,0 +1,416 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of substrate-subxt.
+//
+// subxt is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// subxt is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-subxt.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Machine-readable export of the metadata, including documentation.
+
+use frame_metadata::StorageEntryType;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use super::{
+    diff::type_name,
+    Metadata,
+    ModuleMetadata,
+    StorageMetadata,
+};
+
+/// The metadata of a runtime, with the modules ordered by index.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct MetadataExport {
+    /// The modules of the runtime.
+    pub modules: Vec<ModuleExport>,
+}
+
+/// The metadata of a module.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ModuleExport {
+    /// Name of the module.
+    pub name: String,
+    /// Index of the module.
+    pub index: u8,
+    /// Storage entries, ordered by name.
+    pub storage: Vec<StorageExport>,
+    /// Calls, ordered by index.
+    pub calls: Vec<CallExport>,
+    /// Events, ordered by index.
+    pub events: Vec<EventExport>,
+    /// Constants, ordered by name.
+    pub constants: Vec<ConstantExport>,
+    /// Errors, ordered by index.
+    pub errors: Vec<ErrorExport>,
+}
+
+/// The metadata of a storage entry.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct StorageExport {
+    /// Name of the storage entry.
+    pub name: String,
+    /// `Optional` or `Default`.
+    pub modifier: String,
+    /// Hashers of the keys, empty for plain storage entries.
+    pub hashers: Vec<String>,
+    /// Types of the keys, empty for plain storage entries.
+    pub keys: Vec<String>,
+    /// Type of the value.
+    pub value: String,
+    /// Encoded default value, as a `0x` prefixed hex string.
+    pub default: String,
+    /// Documentation of the storage entry.
+    pub documentation: Vec<String>,
+}
+
+/// The metadata of a call.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct CallExport {
+    /// Name of the call.
+    pub name: String,
+    /// Index of the call within the module.
+    pub index: u8,
+    /// Arguments of the call.
+    pub arguments: Vec<ArgumentExport>,
+    /// Documentation of the call.
+    pub documentation: Vec<String>,
+}
+
+/// A named argument of a call.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ArgumentExport {
+    /// Name of the argument.
+    pub name: String,
+    /// Type of the argument, as written in the metadata.
+    pub ty: String,
+}
+
+/// The metadata of an event.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct EventExport {
+    /// Name of the event.
+    pub name: String,
+    /// Index of the event within the module.
+    pub index: u8,
+    /// Types of the arguments, as written in the metadata.
+    pub arguments: Vec<String>,
+    /// Documentation of the event.
+    pub documentation: Vec<String>,
+}
+
+/// The metadata of a constant.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ConstantExport {
+    /// Name of the constant.
+    pub name: String,
+    /// Type of the constant.
+    pub ty: String,
+    /// Encoded value, as a `0x` prefixed hex string.
+    pub value: String,
+    /// Documentation of the constant.
+    pub documentation: Vec<String>,
+}
+
+/// The metadata of an error.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ErrorExport {
+    /// Name of the error.
+    pub name: String,
+    /// Index of the error within the module.
+    pub index: u8,
+    /// Documentation of the error.
+    pub documentation: Vec<String>,
+}
+
+impl Metadata {
+    /// Returns the metadata in a serializable form.
+    pub fn export(&self) -> MetadataExport {
+        let mut modules = self
+            .modules
+            .values()
+            .map(|module| self.export_module(module))
+            .collect::<Vec<_>>();
+        modules.sort_by_key(|module| module.index);
+        MetadataExport { modules }
+    }
+
+    /// Serializes the metadata as JSON.
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(&self.export())
+    }
+
+    fn export_module(&self, module: &ModuleMetadata) -> ModuleExport {
+        let mut storage = module
+            .storage
+            .iter()
+            .map(|(name, entry)| export_storage(name, entry))
+            .collect::<Vec<_>>();
+        storage.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut calls = self
+            .modules_with_calls
+            .get(&module.name)
+            .into_iter()
+            .flat_map(|module| module.calls.values())
+            .map(|call| {
+                CallExport {
+                    name: call.name.clone(),
+                    index: call.index,
+                    arguments: call
+                        .arguments
+                        .iter()
+                        .map(|arg| {
+                            ArgumentExport {
+                                name: arg.name.clone(),
+                                ty: arg.ty.clone(),
+                            }
+                        })
+                        .collect(),
+                    documentation: call.documentation.clone(),
+                }
+            })
+            .collect::<Vec<_>>();
+        calls.sort_by_key(|call| call.index);
+
+        let mut events = self
+            .modules_with_events
+            .get(&module.name)
+            .into_iter()
+            .flat_map(|module| module.events.iter())
+            .map(|(index, event)| {
+                EventExport {
+                    name: event.name.clone(),
+                    index: *index,
+                    arguments: event.type_names.clone(),
+                    documentation: event.documentation.clone(),
+                }
+            })
+            .collect::<Vec<_>>();
+        events.sort_by_key(|event| event.index);
+
+        let mut constants = module
+            .constants
+            .values()
+            .map(|constant| {
+                ConstantExport {
+                    name: constant.name.clone(),
+                    ty: constant.ty.clone(),
+                    value: to_hex(&constant.value),
+                    documentation: constant.documentation.clone(),
+                }
+            })
+            .collect::<Vec<_>>();
+        constants.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut errors = self
+            .modules_with_errors
+            .get(&module.name)
+            .into_iter()
+            .flat_map(|module| module.errors.iter())
+            .map(|(index, error)| {
+                ErrorExport {
+                    name: error.name.clone(),
+                    index: *index,
+                    documentation: error.documentation.clone(),
+                }
+            })
+            .collect::<Vec<_>>();
+        errors.sort_by_key(|error| error.index);
+
+        ModuleExport {
+            name: module.name.clone(),
+            index: module.index,
+            storage,
+            calls,
+            events,
+            constants,
+            errors,
+        }
+    }
+}
+
+fn export_storage(name: &str, entry: &StorageMetadata) -> StorageExport {
+    let (hashers, keys, value) = match &entry.ty {
+        StorageEntryType::Plain(value) => (vec![], vec![], value),
+        StorageEntryType::Map {
+            hasher, key, value, ..
+        } => (vec![hasher], vec![type_name(key)], value),
+        StorageEntryType::DoubleMap {
+            hasher,
+            key1,
+            key2,
+            value,
+            key2_hasher,
+        } => {
+            (
+                vec![hasher, key2_hasher],
+                vec![type_name(key1), type_name(key2)],
+                value,
+            )
+        }
+    };
+    StorageExport {
+        name: name.to_string(),
+        modifier: format!("{:?}", entry.modifier),
+        hashers: hashers
+            .into_iter()
+            .map(|hasher| format!("{:?}", hasher))
+            .collect(),
+        keys,
+        value: type_name(value),
+        default: to_hex(&entry.default),
+        documentation: entry.documentation.clone(),
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use frame_metadata::{
+        DecodeDifferent,
+        ErrorMetadata,
+        EventMetadata,
+        ExtrinsicMetadata,
+        FunctionArgumentMetadata,
+        FunctionMetadata,
+        ModuleConstantMetadata,
+        RuntimeMetadata,
+        RuntimeMetadataPrefixed,
+        RuntimeMetadataV12,
+        StorageEntryMetadata,
+        StorageEntryModifier,
+        StorageHasher,
+        META_RESERVED,
+    };
+    use std::convert::TryFrom;
+
+    fn docs(doc: &str) -> DecodeDifferent<&'static [&'static str], Vec<String>> {
+        DecodeDifferent::Decoded(vec![doc.to_string()])
+    }
+
+    fn decoded<O>(value: O) -> DecodeDifferent<&'static str, O> {
+        DecodeDifferent::Decoded(value)
+    }
+
+    #[test]
+    fn test_export_metadata() {
+        let module = frame_metadata::ModuleMetadata {
+            name: DecodeDifferent::Decoded("Balances".to_string()),
+            storage: Some(DecodeDifferent::Decoded(frame_metadata::StorageMetadata {
+                prefix: DecodeDifferent::Decoded("Balances".to_string()),
+                entries: DecodeDifferent::Decoded(vec![StorageEntryMetadata {
+                    name: decoded("Account".to_string()),
+                    modifier: StorageEntryModifier::Default,
+                    ty: StorageEntryType::Map {
+                        hasher: StorageHasher::Blake2_128Concat,
+                        key: decoded("T::AccountId".to_string()),
+                        value: decoded("AccountData<T::Balance>".to_string()),
+                        unused: false,
+                    },
+                    default: DecodeDifferent::Decoded(vec![0]),
+                    documentation: docs(" The balance of an account."),
+                }]),
+            })),
+            calls: Some(DecodeDifferent::Decoded(vec![FunctionMetadata {
+                name: decoded("transfer".to_string()),
+                arguments: DecodeDifferent::Decoded(vec![FunctionArgumentMetadata {
+                    name: decoded("value".to_string()),
+                    ty: decoded("Compact<T::Balance>".to_string()),
+                }]),
+                documentation: docs(" Transfer some liquid free balance."),
+            }])),
+            event: Some(DecodeDifferent::Decoded(vec![EventMetadata {
+                name: decoded("Transfer".to_string()),
+                arguments: DecodeDifferent::Decoded(vec!["AccountId".to_string()]),
+                documentation: docs(" Transfer succeeded."),
+            }])),
+            constants: DecodeDifferent::Decoded(vec![ModuleConstantMetadata {
+                name: decoded("ExistentialDeposit".to_string()),
+                ty: decoded("T::Balance".to_string()),
+                value: DecodeDifferent::Decoded(vec![1, 0]),
+                documentation: docs(" The minimum balance."),
+            }]),
+            errors: DecodeDifferent::Decoded(vec![ErrorMetadata {
+                name: decoded("InsufficientBalance".to_string()),
+                documentation: docs(" Balance too low."),
+            }]),
+            index: 5,
+        };
+        let metadata = Metadata::try_from(RuntimeMetadataPrefixed(
+            META_RESERVED,
+            RuntimeMetadata::V12(RuntimeMetadataV12 {
+                modules: DecodeDifferent::Decoded(vec![module]),
+                extrinsic: ExtrinsicMetadata {
+                    version: 4,
+                    signed_extensions: vec![],
+                },
+            }),
+        ))
+        .unwrap();
+
+        let export = metadata.export();
+        let expected = MetadataExport {
+            modules: vec![ModuleExport {
+                name: "Balances".into(),
+                index: 5,
+                storage: vec![StorageExport {
+                    name: "Account".into(),
+                    modifier: "Default".into(),
+                    hashers: vec!["Blake2_128Concat".into()],
+                    keys: vec!["T::AccountId".into()],
+                    value: "AccountData<T::Balance>".into(),
+                    default: "0x00".into(),
+                    documentation: vec![" The balance of an account.".into()],
+                }],
+                calls: vec![CallExport {
+                    name: "transfer".into(),
+                    index: 0,
+                    arguments: vec![ArgumentExport {
+                        name: "value".into(),
+                        ty: "Compact<T::Balance>".into(),
+                    }],
+                    documentation: vec![" Transfer some liquid free balance.".into()],
+                }],
+                events: vec![EventExport {
+                    name: "Transfer".into(),
+                    index: 0,
+                    arguments: vec!["AccountId".into()],
+                    documentation: vec![" Transfer succeeded.".into()],
+                }],
+                constants: vec![ConstantExport {
+                    name: "ExistentialDeposit".into(),
+                    ty: "T::Balance".into(),
+                    value: "0x0100".into(),
+                    documentation: vec![" The minimum balance.".into()],
+                }],
+                errors: vec![ErrorExport {
+                    name: "InsufficientBalance".into(),
+                    index: 0,
+                    documentation: vec![" Balance too low.".into()],
+                }],
+            }],
+        };
+        assert_eq!(export, expected);
+
+        let json = metadata.to_json().unwrap();
+        assert_eq!(
+            serde_json::from_str::<MetadataExport>(&json).unwrap(),
+            expected
+        );
+    }
+}
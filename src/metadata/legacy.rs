@@ -0,0 +1,117 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of substrate-subxt.
+//
+// subxt is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// subxt is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-subxt.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Metadata versions which `frame-metadata` no longer decodes.
+//!
+//! Apart from the module index added in V12 the encoding of the module metadata did not
+//! change since V10, so the `frame-metadata` types are reused for everything below the
+//! module level. V10 lacks the `Identity` storage hasher, which is the last variant of
+//! `StorageHasher` and therefore does not affect decoding.
+
+use codec::Decode;
+use frame_metadata::{
+    DecodeDifferent,
+    ErrorMetadata,
+    EventMetadata,
+    ExtrinsicMetadata,
+    FunctionMetadata,
+    ModuleConstantMetadata,
+    ModuleMetadata,
+    StorageMetadata,
+};
+
+use super::{
+    ConversionError,
+    IntoModules,
+    ModuleIndices,
+};
+
+/// Metadata V10.
+#[derive(Decode)]
+pub struct RuntimeMetadataV10 {
+    modules: Vec<LegacyModuleMetadata>,
+}
+
+/// Metadata V11.
+#[derive(Decode)]
+pub struct RuntimeMetadataV11 {
+    modules: Vec<LegacyModuleMetadata>,
+    #[allow(dead_code)]
+    extrinsic: ExtrinsicMetadata,
+}
+
+/// Module metadata of V10 and V11.
+#[derive(Decode)]
+struct LegacyModuleMetadata {
+    name: String,
+    storage: Option<StorageMetadata>,
+    calls: Option<Vec<FunctionMetadata>>,
+    event: Option<Vec<EventMetadata>>,
+    constants: Vec<ModuleConstantMetadata>,
+    errors: Vec<ErrorMetadata>,
+}
+
+impl IntoModules for RuntimeMetadataV10 {
+    fn into_modules(
+        self,
+    ) -> Result<Vec<(ModuleMetadata, ModuleIndices)>, ConversionError> {
+        Ok(into_modules(self.modules))
+    }
+}
+
+impl IntoModules for RuntimeMetadataV11 {
+    fn into_modules(
+        self,
+    ) -> Result<Vec<(ModuleMetadata, ModuleIndices)>, ConversionError> {
+        Ok(into_modules(self.modules))
+    }
+}
+
+/// Converts the modules and assigns the indices which are implicit before V12.
+///
+/// Modules are indexed by their position in the runtime, while the outer call and event
+/// enums only have variants for the modules with calls or events.
+fn into_modules(
+    modules: Vec<LegacyModuleMetadata>,
+) -> Vec<(ModuleMetadata, ModuleIndices)> {
+    let mut call_index = 0;
+    let mut event_index = 0;
+    let mut converted = Vec::new();
+    for (index, module) in modules.into_iter().enumerate() {
+        let indices = ModuleIndices {
+            module: index as u8,
+            calls: call_index,
+            events: event_index,
+        };
+        if module.calls.is_some() {
+            call_index += 1;
+        }
+        if module.event.is_some() {
+            event_index += 1;
+        }
+        let module = ModuleMetadata {
+            name: DecodeDifferent::Decoded(module.name),
+            storage: module.storage.map(DecodeDifferent::Decoded),
+            calls: module.calls.map(DecodeDifferent::Decoded),
+            event: module.event.map(DecodeDifferent::Decoded),
+            constants: DecodeDifferent::Decoded(module.constants),
+            errors: DecodeDifferent::Decoded(module.errors),
+            index: index as u8,
+        };
+        converted.push((module, indices));
+    }
+    converted
+}
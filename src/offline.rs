@@ -0,0 +1,179 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of substrate-subxt.
+//
+// subxt is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// subxt is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-subxt.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Chain information saved to a file, so that a client can be built without
+//! fetching it from a node.
+
+use codec::{
+    Decode,
+    Encode,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use sp_version::RuntimeVersion;
+use std::path::Path;
+
+use crate::{
+    error::Error,
+    metadata::Metadata,
+    rpc::SystemProperties,
+};
+
+/// The chain information a `Client` fetches on startup.
+///
+/// Can be saved as SCALE or JSON and passed to
+/// [`ClientBuilder::set_offline_metadata`](crate::ClientBuilder::set_offline_metadata)
+/// to skip the RPC requests when building a client.
+#[derive(Clone, Debug, Encode, Decode, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OfflineMetadata<Hash> {
+    /// Genesis hash of the chain.
+    pub genesis_hash: Hash,
+    /// Runtime version of the chain.
+    pub runtime_version: RuntimeVersion,
+    /// System properties of the chain.
+    pub properties: SystemProperties,
+    /// Runtime metadata of the chain.
+    pub metadata: Metadata,
+}
+
+impl<Hash> OfflineMetadata<Hash>
+where
+    Hash: Encode + Decode + Serialize + for<'de> Deserialize<'de>,
+{
+    /// Parses the chain information from JSON.
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Serializes the chain information as JSON.
+    pub fn to_json(&self) -> Result<String, Error> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Decodes the chain information from SCALE.
+    pub fn from_scale(mut bytes: &[u8]) -> Result<Self, Error> {
+        Ok(Decode::decode(&mut bytes)?)
+    }
+
+    /// Encodes the chain information as SCALE.
+    pub fn to_scale(&self) -> Vec<u8> {
+        self.encode()
+    }
+
+    /// Loads the chain information from a file.
+    ///
+    /// Files with a `.json` extension are parsed as JSON, all others as SCALE.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        if is_json(path) {
+            Self::from_json(&std::fs::read_to_string(path)?)
+        } else {
+            Self::from_scale(&std::fs::read(path)?)
+        }
+    }
+
+    /// Saves the chain information to a file.
+    ///
+    /// Files with a `.json` extension are written as JSON, all others as SCALE.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let path = path.as_ref();
+        let contents = if is_json(path) {
+            self.to_json()?.into_bytes()
+        } else {
+            self.to_scale()
+        };
+        std::fs::write(path, contents)?;
+        Ok(())
+    }
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension().map(|ext| ext == "json").unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use frame_metadata::{
+        DecodeDifferent,
+        ExtrinsicMetadata,
+        ModuleMetadata,
+        RuntimeMetadata,
+        RuntimeMetadataPrefixed,
+        RuntimeMetadataV12,
+        META_RESERVED,
+    };
+    use sp_core::H256;
+    use std::convert::TryFrom;
+
+    fn offline_metadata() -> OfflineMetadata<H256> {
+        let metadata = Metadata::try_from(RuntimeMetadataPrefixed(
+            META_RESERVED,
+            RuntimeMetadata::V12(RuntimeMetadataV12 {
+                modules: DecodeDifferent::Decoded(vec![ModuleMetadata {
+                    name: DecodeDifferent::Decoded("System".to_string()),
+                    storage: None,
+                    calls: None,
+                    event: None,
+                    constants: DecodeDifferent::Decoded(vec![]),
+                    errors: DecodeDifferent::Decoded(vec![]),
+                    index: 0,
+                }]),
+                extrinsic: ExtrinsicMetadata {
+                    version: 4,
+                    signed_extensions: vec![],
+                },
+            }),
+        ))
+        .unwrap();
+        OfflineMetadata {
+            genesis_hash: H256::repeat_byte(1),
+            runtime_version: RuntimeVersion {
+                spec_version: 7,
+                transaction_version: 2,
+                ..Default::default()
+            },
+            properties: SystemProperties {
+                ss58_format: 42,
+                token_decimals: 12,
+                token_symbol: "UNIT".into(),
+            },
+            metadata,
+        }
+    }
+
+    fn assert_same(a: &OfflineMetadata<H256>, b: &OfflineMetadata<H256>) {
+        assert_eq!(a.genesis_hash, b.genesis_hash);
+        assert_eq!(a.runtime_version, b.runtime_version);
+        assert_eq!(a.properties, b.properties);
+        assert_eq!(a.metadata.encoded(), b.metadata.encoded());
+        assert!(b.metadata.module("System").is_ok());
+    }
+
+    #[test]
+    fn test_offline_metadata_roundtrip() {
+        let offline = offline_metadata();
+
+        let scale = OfflineMetadata::from_scale(&offline.to_scale()).unwrap();
+        assert_same(&offline, &scale);
+
+        let json = OfflineMetadata::from_json(&offline.to_json().unwrap()).unwrap();
+        assert_same(&offline, &json);
+    }
+}
@@ -0,0 +1,362 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of substrate-subxt.
+//
+// subxt is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// subxt is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-subxt.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Following a submitted transaction through the transaction pool and the chain.
+
+use futures::{
+    future::Future,
+    pin_mut,
+    stream::Stream,
+    task::{
+        Context,
+        Poll,
+    },
+};
+use jsonrpsee::client::Subscription;
+use sp_runtime::traits::Hash;
+use std::pin::Pin;
+
+use crate::{
+    error::Error,
+    events::{
+        EventsDecoder,
+        Raw,
+    },
+    frame::system::{
+        Phase,
+        System,
+    },
+    rpc::{
+        ExtrinsicSuccess,
+        TransactionStatus,
+    },
+    runtimes::Runtime,
+    Client,
+};
+
+/// Progress of a submitted transaction.
+///
+/// Returns every status the node reports for the transaction, until a status after
+/// which the node stops reporting, e.g. `Finalized` or `Dropped`. It is also a `Stream`
+/// of the statuses.
+///
+/// The subscription to the statuses does not end when the connection to the node is
+/// closed, so waiting for the next status never completes then. Wrap the waiting in a
+/// timeout to give up on a node which went away.
+pub struct TransactionProgress<T: Runtime> {
+    client: Client<T>,
+    subscription: Subscription<TransactionStatus<T::Hash, T::Hash>>,
+    extrinsic: T::Hash,
+    decoder: EventsDecoder<T>,
+    runtime_decoder: Option<(u32, EventsDecoder<T>)>,
+    finished: bool,
+    tracker: InclusionTracker<T::Hash>,
+}
+
+impl<T: Runtime> TransactionProgress<T> {
+    pub(crate) fn new(
+        client: Client<T>,
+        subscription: Subscription<TransactionStatus<T::Hash, T::Hash>>,
+        extrinsic: T::Hash,
+        decoder: EventsDecoder<T>,
+    ) -> Self {
+        Self {
+            client,
+            subscription,
+            extrinsic,
+            decoder,
+            runtime_decoder: None,
+            finished: false,
+            tracker: InclusionTracker::new(),
+        }
+    }
+
+    /// Returns the hash of the transaction.
+    pub fn extrinsic_hash(&self) -> T::Hash {
+        self.extrinsic
+    }
+
+    /// Gets the next status of the transaction.
+    ///
+    /// A status which `next_inclusion` held back to report a reorg first is returned
+    /// before the statuses reported after it.
+    pub async fn next(&mut self) -> Option<TransactionStatus<T::Hash, T::Hash>> {
+        if let Some(status) = self.tracker.pending.take() {
+            return Some(status)
+        }
+        if self.finished {
+            return None
+        }
+        let status = self.subscription.next().await;
+        log::info!("Extrinsic {:?} status {:?}", self.extrinsic, status);
+        self.finished = status.is_final();
+        Some(status)
+    }
+
+    /// Waits for the transaction to be included in a block and returns its events
+    /// in that block.
+    ///
+    /// The block may still be retracted, use `wait_for_finalized` or `next_inclusion`
+    /// before acting on the events irreversibly. Fails if the final status was
+    /// returned already.
+    pub async fn wait_for_in_block(mut self) -> Result<ExtrinsicSuccess<T>, Error> {
+        loop {
+            match self.next().await {
+                Some(TransactionStatus::InBlock(block))
+                | Some(TransactionStatus::Finalized(block)) => {
+                    return self.events_in(block).await
+                }
+                Some(status) => check_status(status)?,
+                None => return Err(self.ended()),
+            }
+        }
+    }
+
+    /// Waits for the block including the transaction to be finalized and returns the
+    /// events of the transaction in that block. Fails if the final status was returned
+    /// already.
+    pub async fn wait_for_finalized(mut self) -> Result<ExtrinsicSuccess<T>, Error> {
+        loop {
+            match self.next().await {
+                Some(TransactionStatus::Finalized(block)) => {
+                    return self.events_in(block).await
+                }
+                Some(status) => check_status(status)?,
+                None => return Err(self.ended()),
+            }
+        }
+    }
+
+    /// Gets the next inclusion of the transaction in a block, following it through
+    /// retractions and re-inclusions until the block including it is finalized.
+    ///
+    /// Returns `Inclusion::Reorged` when the block of the events returned before is no
+    /// longer canonical, and lastly `Inclusion::Finalized` with the events in the final
+    /// canonical block.
+    pub async fn next_inclusion(&mut self) -> Option<Result<Inclusion<T>, Error>> {
+        loop {
+            let status = self.next().await?;
+            let inclusion = match self.tracker.step(status) {
+                Ok(Step::InBlock(block)) => {
+                    self.events_in(block).await.map(Inclusion::InBlock)
+                }
+                Ok(Step::Finalized(block)) => {
+                    self.events_in(block).await.map(Inclusion::Finalized)
+                }
+                Ok(Step::Reorged(block)) => Ok(Inclusion::Reorged(block)),
+                Ok(Step::Wait) => continue,
+                Err(error) => Err(error),
+            };
+            return Some(inclusion)
+        }
+    }
+
+    fn ended(&self) -> Error {
+        Error::Other(format!(
+            "Final status of extrinsic {:?} returned already",
+            self.extrinsic
+        ))
+    }
+
+    /// Fetches the events emitted by the transaction in a block, decoded with the
+    /// metadata of the runtime of the block.
+    async fn events_in(
+        &mut self,
+        block_hash: T::Hash,
+    ) -> Result<ExtrinsicSuccess<T>, Error> {
+        log::info!("Fetching block {:?}", block_hash);
+        let block = self.client.block(Some(block_hash)).await?.ok_or_else(|| {
+            Error::Other(format!("Failed to find block {:?}", block_hash))
+        })?;
+        let ext_index = block
+            .block
+            .extrinsics
+            .iter()
+            .position(|ext| <T as System>::Hashing::hash_of(ext) == self.extrinsic)
+            .ok_or_else(|| {
+                Error::Other(format!(
+                    "Failed to find Extrinsic with hash {:?}",
+                    self.extrinsic,
+                ))
+            })?;
+        let mut events = Vec::new();
+        if let Some(data) = self.client.rpc.events(block_hash).await? {
+            let decoder = self
+                .client
+                .decoder_at(block_hash, &self.decoder, &mut self.runtime_decoder)
+                .await?;
+            for (phase, raw) in decoder.decode_events(&mut &data.0[..])? {
+                let is_transaction = match phase {
+                    Phase::ApplyExtrinsic(i) => i as usize == ext_index,
+                    _ => false,
+                };
+                match raw {
+                    Raw::Event(record) if is_transaction => events.push(record.event),
+                    Raw::Error(err) if is_transaction => return Err(err.into()),
+                    // the records after an undecoded one are skipped, so whether the
+                    // transaction succeeded is unknown whatever the phase
+                    Raw::Undecoded(undecoded) => {
+                        return Err(Error::Undecoded(Box::new(undecoded)))
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Ok(ExtrinsicSuccess {
+            block: block_hash,
+            extrinsic: self.extrinsic,
+            events,
+        })
+    }
+}
+
+// the progress is never pinned in place, the futures of `next` only borrow it
+impl<T: Runtime> Unpin for TransactionProgress<T> {}
+
+impl<T: Runtime> Stream for TransactionProgress<T> {
+    type Item = TransactionStatus<T::Hash, T::Hash>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        // dropping a pending `next` does not lose a status, the subscription keeps it
+        // until it is polled again
+        let next = self.next();
+        pin_mut!(next);
+        next.poll(cx)
+    }
+}
+
+/// Inclusion of a transaction in a block, returned by
+/// `TransactionProgress::next_inclusion`.
+#[derive(Debug)]
+pub enum Inclusion<T: System> {
+    /// The transaction was included in a block, which may still be retracted.
+    InBlock(ExtrinsicSuccess<T>),
+    /// The block with the given hash was retracted, so the events returned for it are
+    /// no longer valid.
+    Reorged(T::Hash),
+    /// The block including the transaction was finalized.
+    Finalized(ExtrinsicSuccess<T>),
+}
+
+/// What to report for a status of the transaction.
+#[derive(Debug, Eq, PartialEq)]
+enum Step<H> {
+    /// The events in a block.
+    InBlock(H),
+    /// The events in a finalized block.
+    Finalized(H),
+    /// The events in a block are no longer valid.
+    Reorged(H),
+    /// Nothing, wait for the next status.
+    Wait,
+}
+
+/// Tracks the block whose events were reported last, to report when they are no longer
+/// valid.
+struct InclusionTracker<H> {
+    reported: Option<H>,
+    /// A status to step through again after reporting a reorg, returned by the next
+    /// call of `TransactionProgress::next`.
+    pending: Option<TransactionStatus<H, H>>,
+}
+
+impl<H: Copy + PartialEq> InclusionTracker<H> {
+    fn new() -> Self {
+        Self {
+            reported: None,
+            pending: None,
+        }
+    }
+
+    fn step(&mut self, status: TransactionStatus<H, H>) -> Result<Step<H>, Error> {
+        let included = match &status {
+            TransactionStatus::InBlock(block) | TransactionStatus::Finalized(block) => {
+                Some(*block)
+            }
+            _ => None,
+        };
+        if let (Some(block), Some(reported)) = (included, self.reported) {
+            if block != reported {
+                // included in a block of another fork without the reported block being
+                // retracted first
+                self.reported = None;
+                self.pending = Some(status);
+                return Ok(Step::Reorged(reported))
+            }
+        }
+        match status {
+            TransactionStatus::InBlock(block) => {
+                self.reported = Some(block);
+                Ok(Step::InBlock(block))
+            }
+            TransactionStatus::Finalized(block) => {
+                self.reported = Some(block);
+                Ok(Step::Finalized(block))
+            }
+            TransactionStatus::Retracted(block) if self.reported == Some(block) => {
+                self.reported = None;
+                Ok(Step::Reorged(block))
+            }
+            status => check_status(status).map(|_| Step::Wait),
+        }
+    }
+}
+
+/// Returns an error for the statuses after which the transaction can not be included
+/// anymore.
+fn check_status<H, B>(status: TransactionStatus<H, B>) -> Result<(), Error> {
+    match status {
+        TransactionStatus::Invalid => Err("Extrinsic Invalid".into()),
+        TransactionStatus::Usurped(_) => Err("Extrinsic Usurped".into()),
+        TransactionStatus::Dropped => Err("Extrinsic Dropped".into()),
+        TransactionStatus::FinalityTimeout(_) => Err("Extrinsic FinalityTimeout".into()),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inclusion_tracker() {
+        let mut tracker = InclusionTracker::new();
+        assert_eq!(tracker.step(TransactionStatus::Ready).unwrap(), Step::Wait);
+        assert_eq!(
+            tracker.step(TransactionStatus::InBlock(1)).unwrap(),
+            Step::InBlock(1)
+        );
+        assert_eq!(
+            tracker.step(TransactionStatus::Retracted(1)).unwrap(),
+            Step::Reorged(1)
+        );
+        assert_eq!(
+            tracker.step(TransactionStatus::InBlock(2)).unwrap(),
+            Step::InBlock(2)
+        );
+        assert_eq!(
+            tracker.step(TransactionStatus::Retracted(1)).unwrap(),
+            Step::Wait
+        );
+        assert_eq!(
+            tracker.step(TransactionStatus::Finalized(3)).unwrap(),
+            Step::Reorged(2)
+        );
+        let pending = tracker.pending.take().unwrap();
+        assert_eq!(tracker.step(pending).unwrap(), Step::Finalized(3));
+        assert!(tracker.step(TransactionStatus::Dropped).is_err());
+    }
+}
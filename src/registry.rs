@@ -0,0 +1,429 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of substrate-subxt.
+//
+// subxt is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// subxt is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-subxt.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Type definitions loaded from a type registry file.
+
+use serde::{
+    de::{
+        MapAccess,
+        Visitor,
+    },
+    Deserialize,
+    Deserializer,
+};
+use std::{
+    collections::{
+        HashMap,
+        HashSet,
+    },
+    fmt,
+    path::Path,
+};
+
+use crate::metadata::{
+    ConversionError,
+    EventArg,
+};
+
+/// Type registry error.
+#[derive(Debug, thiserror::Error)]
+pub enum TypeRegistryError {
+    /// Io error.
+    #[error("Io error: {0}")]
+    Io(#[from] std::io::Error),
+    /// Json error.
+    #[error("Json error: {0}")]
+    Json(#[from] serde_json::Error),
+    /// Toml error.
+    #[error("Toml error: {0}")]
+    Toml(#[from] toml::de::Error),
+    /// Type definition could not be parsed.
+    #[error("Invalid definition of type {0}: {1}")]
+    InvalidType(String, ConversionError),
+    /// Following the aliases from a type leads back to it.
+    #[error("Alias cycle in the definition of type {0}")]
+    AliasCycle(String),
+}
+
+/// Type definitions for decoding events of custom chains, loaded from a JSON or TOML file.
+///
+/// Each type is either an alias of another type, a struct with named fields or an enum,
+/// using the same type syntax as the metadata:
+///
+/// ```json
+/// {
+///     "types": {
+///         "Balance": "u128",
+///         "KittyIndex": "Compact<u32>",
+///         "Dna": "[u8; 16]",
+///         "Kitty": { "dna": "Dna", "price": "Option<Balance>" },
+///         "Gender": { "_enum": ["Male", "Female"] },
+///         "Offer": { "_enum": { "None": "Null", "Fixed": "Balance" } }
+///     },
+///     "versions": [
+///         { "min": 0, "max": 10, "types": { "Balance": "u64" } }
+///     ]
+/// }
+/// ```
+///
+/// The `versions` entries override the common `types` for the spec versions in the
+/// inclusive range between `min` and `max`, either of which may be omitted.
+#[derive(Clone, Debug, Default)]
+pub struct TypeRegistry {
+    types: HashMap<String, TypeDef>,
+    versions: Vec<VersionedTypes>,
+}
+
+#[derive(Clone, Debug)]
+struct VersionedTypes {
+    min: Option<u32>,
+    max: Option<u32>,
+    types: HashMap<String, TypeDef>,
+}
+
+impl VersionedTypes {
+    fn contains(&self, spec_version: u32) -> bool {
+        self.min.map(|min| min <= spec_version).unwrap_or(true)
+            && self.max.map(|max| spec_version <= max).unwrap_or(true)
+    }
+}
+
+impl TypeRegistry {
+    /// Parses a type registry from JSON.
+    pub fn from_json(json: &str) -> Result<Self, TypeRegistryError> {
+        Self::from_file_types(serde_json::from_str(json)?)
+    }
+
+    /// Parses a type registry from TOML.
+    pub fn from_toml(toml: &str) -> Result<Self, TypeRegistryError> {
+        Self::from_file_types(toml::from_str(toml)?)
+    }
+
+    /// Loads a type registry from a file.
+    ///
+    /// Files with a `.toml` extension are parsed as TOML, all others as JSON.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, TypeRegistryError> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)?;
+        if path.extension().map(|ext| ext == "toml").unwrap_or(false) {
+            Self::from_toml(&contents)
+        } else {
+            Self::from_json(&contents)
+        }
+    }
+
+    fn from_file_types(file: RegistryFile) -> Result<Self, TypeRegistryError> {
+        let versions = file
+            .versions
+            .into_iter()
+            .map(|versioned| {
+                Ok(VersionedTypes {
+                    min: versioned.min,
+                    max: versioned.max,
+                    types: convert_types(versioned.types)?,
+                })
+            })
+            .collect::<Result<_, TypeRegistryError>>()?;
+        let registry = Self {
+            types: convert_types(file.types)?,
+            versions,
+        };
+        // the types only change where the range of a version starts or ends, so
+        // checking those spec versions covers all of them
+        let mut spec_versions = vec![0];
+        for versioned in &registry.versions {
+            spec_versions.extend(versioned.min);
+            spec_versions.extend(versioned.max.and_then(|max| max.checked_add(1)));
+        }
+        for spec_version in spec_versions {
+            check_alias_cycles(&registry.types(spec_version))?;
+        }
+        Ok(registry)
+    }
+
+    /// Returns the type definitions for a spec version.
+    pub(crate) fn types(&self, spec_version: u32) -> HashMap<String, TypeDef> {
+        let mut types = self.types.clone();
+        for versioned in &self.versions {
+            if versioned.contains(spec_version) {
+                types.extend(versioned.types.clone());
+            }
+        }
+        types
+    }
+}
+
+/// Type definition.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) enum TypeDef {
+    /// Another name for a type.
+    Alias(EventArg),
+    /// Struct with named fields, in encoding order.
+    Struct(Vec<(String, EventArg)>),
+    /// Enum with named variants, in index order.
+    Enum(Vec<(String, EventArg)>),
+}
+
+impl TypeDef {
+    /// Returns the types this definition refers to.
+    pub(crate) fn args(&self) -> Vec<&EventArg> {
+        match self {
+            TypeDef::Alias(arg) => vec![arg],
+            TypeDef::Struct(fields) | TypeDef::Enum(fields) => {
+                fields.iter().map(|(_, arg)| arg).collect()
+            }
+        }
+    }
+}
+
+/// Fails if following the aliases from a type leads back to it, which would never
+/// finish decoding.
+fn check_alias_cycles(types: &HashMap<String, TypeDef>) -> Result<(), TypeRegistryError> {
+    for name in types.keys() {
+        let mut seen = HashSet::new();
+        let mut current = name.clone();
+        while let Some(TypeDef::Alias(arg)) = types.get(&current) {
+            if !seen.insert(current) {
+                return Err(TypeRegistryError::AliasCycle(name.clone()))
+            }
+            current = match arg {
+                EventArg::Primitive(_) | EventArg::Generic(_, _) => arg.to_string(),
+                _ => break,
+            };
+        }
+    }
+    Ok(())
+}
+
+fn convert_types(
+    types: HashMap<String, Definition>,
+) -> Result<HashMap<String, TypeDef>, TypeRegistryError> {
+    types
+        .into_iter()
+        .map(|(name, definition)| {
+            let def = convert_definition(definition)
+                .map_err(|err| TypeRegistryError::InvalidType(name.clone(), err))?;
+            Ok((name, def))
+        })
+        .collect()
+}
+
+fn convert_definition(definition: Definition) -> Result<TypeDef, ConversionError> {
+    let parse_fields = |fields: Fields| {
+        fields
+            .0
+            .into_iter()
+            .map(|(name, ty)| Ok((name, parse_type(&ty)?)))
+            .collect::<Result<Vec<_>, ConversionError>>()
+    };
+    let def = match definition {
+        Definition::Alias(ty) => TypeDef::Alias(parse_type(&ty)?),
+        Definition::Enum {
+            variants: Variants::Unit(names),
+        } => {
+            TypeDef::Enum(
+                names
+                    .into_iter()
+                    .map(|name| (name, EventArg::Tuple(vec![])))
+                    .collect(),
+            )
+        }
+        Definition::Enum {
+            variants: Variants::Fields(fields),
+        } => TypeDef::Enum(parse_fields(fields)?),
+        Definition::Struct(fields) => TypeDef::Struct(parse_fields(fields)?),
+    };
+    Ok(def)
+}
+
+fn parse_type(ty: &str) -> Result<EventArg, ConversionError> {
+    match ty.trim() {
+        "Null" => Ok(EventArg::Tuple(vec![])),
+        ty => ty.parse(),
+    }
+}
+
+#[derive(Deserialize)]
+struct RegistryFile {
+    #[serde(default)]
+    types: HashMap<String, Definition>,
+    #[serde(default)]
+    versions: Vec<RegistryFileVersion>,
+}
+
+#[derive(Deserialize)]
+struct RegistryFileVersion {
+    #[serde(default)]
+    min: Option<u32>,
+    #[serde(default)]
+    max: Option<u32>,
+    types: HashMap<String, Definition>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Definition {
+    Alias(String),
+    Enum {
+        #[serde(rename = "_enum")]
+        variants: Variants,
+    },
+    Struct(Fields),
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Variants {
+    Unit(Vec<String>),
+    Fields(Fields),
+}
+
+/// Named types in the order they appear in the file.
+struct Fields(Vec<(String, String)>);
+
+impl<'de> Deserialize<'de> for Fields {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct FieldsVisitor;
+
+        impl<'de> Visitor<'de> for FieldsVisitor {
+            type Value = Fields;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a map of names to types")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(
+                self,
+                mut map: A,
+            ) -> Result<Self::Value, A::Error> {
+                let mut fields = Vec::new();
+                while let Some(field) = map.next_entry()? {
+                    fields.push(field);
+                }
+                Ok(Fields(fields))
+            }
+        }
+
+        deserializer.deserialize_map(FieldsVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn arg(ty: &str) -> EventArg {
+        ty.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_json_registry() {
+        let registry = TypeRegistry::from_json(
+            r#"{
+                "types": {
+                    "Balance": "u128",
+                    "Kitty": { "price": "Option<Balance>", "dna": "[u8; 16]" },
+                    "Gender": { "_enum": ["Male", "Female"] },
+                    "Offer": { "_enum": { "None": "Null", "Fixed": "Balance" } }
+                },
+                "versions": [
+                    { "max": 10, "types": { "Balance": "u64" } }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let types = registry.types(11);
+        assert_eq!(types["Balance"], TypeDef::Alias(arg("u128")));
+        assert_eq!(
+            types["Kitty"],
+            TypeDef::Struct(vec![
+                ("price".into(), arg("Option<Balance>")),
+                ("dna".into(), arg("[u8; 16]")),
+            ])
+        );
+        assert_eq!(
+            types["Gender"],
+            TypeDef::Enum(vec![
+                ("Male".into(), arg("()")),
+                ("Female".into(), arg("()")),
+            ])
+        );
+        assert_eq!(
+            types["Offer"],
+            TypeDef::Enum(vec![
+                ("None".into(), arg("()")),
+                ("Fixed".into(), arg("Balance")),
+            ])
+        );
+
+        assert_eq!(registry.types(10)["Balance"], TypeDef::Alias(arg("u64")));
+    }
+
+    #[test]
+    fn test_parse_toml_registry() {
+        let registry = TypeRegistry::from_toml(
+            r#"
+            [types]
+            Balance = "u128"
+
+            [types.Kitty]
+            price = "Option<Balance>"
+            dna = "[u8; 16]"
+
+            [[versions]]
+            min = 20
+            [versions.types]
+            Balance = "u64"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            registry.types(1)["Kitty"],
+            TypeDef::Struct(vec![
+                ("price".into(), arg("Option<Balance>")),
+                ("dna".into(), arg("[u8; 16]")),
+            ])
+        );
+        assert_eq!(registry.types(1)["Balance"], TypeDef::Alias(arg("u128")));
+        assert_eq!(registry.types(20)["Balance"], TypeDef::Alias(arg("u64")));
+    }
+
+    #[test]
+    fn test_invalid_type() {
+        let result = TypeRegistry::from_json(r#"{ "types": { "Broken": "Vec<u8" } }"#);
+        assert!(matches!(
+            result,
+            Err(TypeRegistryError::InvalidType(name, _)) if name == "Broken"
+        ));
+    }
+
+    #[test]
+    fn test_alias_cycle() {
+        let result = TypeRegistry::from_json(r#"{ "types": { "Balance": "Balance" } }"#);
+        assert!(matches!(result, Err(TypeRegistryError::AliasCycle(_))));
+
+        // the cycle only exists in the types of a version
+        let result = TypeRegistry::from_json(
+            r#"{
+                "types": { "A": "B", "B": "u32" },
+                "versions": [{ "min": 10, "types": { "B": "A" } }]
+            }"#,
+        );
+        assert!(matches!(result, Err(TypeRegistryError::AliasCycle(_))));
+    }
+}
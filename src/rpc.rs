@@ -24,11 +24,7 @@ use codec::{
     Encode,
     Error as CodecError,
 };
-use core::{
-    convert::TryInto,
-    marker::PhantomData,
-};
-use frame_metadata::RuntimeMetadataPrefixed;
+use core::marker::PhantomData;
 use jsonrpsee::{
     client::Subscription,
     common::{
@@ -59,23 +55,19 @@ use sp_runtime::{
         Block,
         SignedBlock,
     },
-    traits::Hash,
+    ApplyExtrinsicResult,
 };
 use sp_version::RuntimeVersion;
 
 use crate::{
     error::Error,
-    events::{
-        EventsDecoder,
-        RawEvent,
-    },
+    events::RawEvent,
     frame::{
         system::System,
         Event,
     },
     metadata::Metadata,
     runtimes::Runtime,
-    subscription::EventSubscription,
 };
 
 pub type ChainBlock<T> =
@@ -98,7 +90,9 @@ impl From<u32> for BlockNumber {
 }
 
 /// System properties for a Substrate-based runtime
-#[derive(serde::Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+#[derive(
+    serde::Serialize, Deserialize, Encode, Decode, Debug, Clone, PartialEq, Eq, Default,
+)]
 #[serde(rename_all = "camelCase")]
 pub struct SystemProperties {
     /// The address format
@@ -142,6 +136,20 @@ pub enum TransactionStatus<Hash, BlockHash> {
     Invalid,
 }
 
+impl<Hash, BlockHash> TransactionStatus<Hash, BlockHash> {
+    /// Returns `true` if the node reports no status after this one.
+    pub fn is_final(&self) -> bool {
+        matches!(
+            self,
+            Self::Finalized(_)
+                | Self::FinalityTimeout(_)
+                | Self::Usurped(_)
+                | Self::Dropped
+                | Self::Invalid
+        )
+    }
+}
+
 /// ReadProof struct returned by the RPC
 ///
 /// # Note
@@ -159,7 +167,7 @@ pub struct ReadProof<Hash> {
 
 /// Client for substrate rpc interfaces
 pub struct Rpc<T: Runtime> {
-    client: Client,
+    client: Option<Client>,
     marker: PhantomData<T>,
 }
 
@@ -175,11 +183,24 @@ impl<T: Runtime> Clone for Rpc<T> {
 impl<T: Runtime> Rpc<T> {
     pub fn new(client: Client) -> Self {
         Self {
-            client,
+            client: Some(client),
+            marker: PhantomData,
+        }
+    }
+
+    /// Creates an rpc client without a node, whose requests fail with
+    /// `Error::Offline`.
+    pub fn offline() -> Self {
+        Self {
+            client: None,
             marker: PhantomData,
         }
     }
 
+    fn client(&self) -> Result<&Client, Error> {
+        self.client.as_ref().ok_or(Error::Offline)
+    }
+
     /// Fetch a storage key
     pub async fn storage(
         &self,
@@ -187,7 +208,7 @@ impl<T: Runtime> Rpc<T> {
         hash: Option<T::Hash>,
     ) -> Result<Option<StorageData>, Error> {
         let params = Params::Array(vec![to_json_value(key)?, to_json_value(hash)?]);
-        let data = self.client.request("state_getStorage", params).await?;
+        let data = self.client()?.request("state_getStorage", params).await?;
         log::debug!("state_getStorage {:?}", data);
         Ok(data)
     }
@@ -208,7 +229,7 @@ impl<T: Runtime> Rpc<T> {
             to_json_value(start_key)?,
             to_json_value(hash)?,
         ]);
-        let data = self.client.request("state_getKeysPaged", params).await?;
+        let data = self.client()?.request("state_getKeysPaged", params).await?;
         log::debug!("state_getKeysPaged {:?}", data);
         Ok(data)
     }
@@ -225,7 +246,7 @@ impl<T: Runtime> Rpc<T> {
             to_json_value(from)?,
             to_json_value(to)?,
         ]);
-        self.client
+        self.client()?
             .request("state_queryStorage", params)
             .await
             .map_err(Into::into)
@@ -238,7 +259,7 @@ impl<T: Runtime> Rpc<T> {
         at: Option<T::Hash>,
     ) -> Result<Vec<StorageChangeSet<<T as System>::Hash>>, Error> {
         let params = Params::Array(vec![to_json_value(keys)?, to_json_value(at)?]);
-        self.client
+        self.client()?
             .request("state_queryStorage", params)
             .await
             .map_err(Into::into)
@@ -249,7 +270,7 @@ impl<T: Runtime> Rpc<T> {
         let block_zero = Some(ListOrValue::Value(NumberOrHex::Number(0)));
         let params = Params::Array(vec![to_json_value(block_zero)?]);
         let list_or_value: ListOrValue<Option<T::Hash>> =
-            self.client.request("chain_getBlockHash", params).await?;
+            self.client()?.request("chain_getBlockHash", params).await?;
         match list_or_value {
             ListOrValue::Value(genesis_hash) => {
                 genesis_hash.ok_or_else(|| "Genesis hash not found".into())
@@ -258,21 +279,17 @@ impl<T: Runtime> Rpc<T> {
         }
     }
 
-    /// Fetch the metadata
-    pub async fn metadata(&self) -> Result<Metadata, Error> {
-        let bytes: Bytes = self
-            .client
-            .request("state_getMetadata", Params::None)
-            .await?;
-        let meta: RuntimeMetadataPrefixed = Decode::decode(&mut &bytes[..])?;
-        let metadata: Metadata = meta.try_into()?;
-        Ok(metadata)
+    /// Fetch the metadata, at the best block if no hash is given.
+    pub async fn metadata(&self, at: Option<T::Hash>) -> Result<Metadata, Error> {
+        let params = Params::Array(vec![to_json_value(at)?]);
+        let bytes: Bytes = self.client()?.request("state_getMetadata", params).await?;
+        Ok(Metadata::from_bytes(&bytes)?)
     }
 
     /// Fetch system properties
     pub async fn system_properties(&self) -> Result<SystemProperties, Error> {
         Ok(self
-            .client
+            .client()?
             .request("system_properties", Params::None)
             .await?)
     }
@@ -283,7 +300,7 @@ impl<T: Runtime> Rpc<T> {
         hash: Option<T::Hash>,
     ) -> Result<Option<T::Header>, Error> {
         let params = Params::Array(vec![to_json_value(hash)?]);
-        let header = self.client.request("chain_getHeader", params).await?;
+        let header = self.client()?.request("chain_getHeader", params).await?;
         Ok(header)
     }
 
@@ -294,7 +311,7 @@ impl<T: Runtime> Rpc<T> {
     ) -> Result<Option<T::Hash>, Error> {
         let block_number = block_number.map(ListOrValue::Value);
         let params = Params::Array(vec![to_json_value(block_number)?]);
-        let list_or_value = self.client.request("chain_getBlockHash", params).await?;
+        let list_or_value = self.client()?.request("chain_getBlockHash", params).await?;
         match list_or_value {
             ListOrValue::Value(hash) => Ok(hash),
             ListOrValue::List(_) => Err("Expected a Value, got a List".into()),
@@ -304,7 +321,7 @@ impl<T: Runtime> Rpc<T> {
     /// Get a block hash of the latest finalized block
     pub async fn finalized_head(&self) -> Result<T::Hash, Error> {
         let hash = self
-            .client
+            .client()?
             .request("chain_getFinalizedHead", Params::None)
             .await?;
         Ok(hash)
@@ -316,7 +333,7 @@ impl<T: Runtime> Rpc<T> {
         hash: Option<T::Hash>,
     ) -> Result<Option<ChainBlock<T>>, Error> {
         let params = Params::Array(vec![to_json_value(hash)?]);
-        let block = self.client.request("chain_getBlock", params).await?;
+        let block = self.client()?.request("chain_getBlock", params).await?;
         Ok(block)
     }
 
@@ -327,7 +344,7 @@ impl<T: Runtime> Rpc<T> {
         hash: Option<T::Hash>,
     ) -> Result<ReadProof<T::Hash>, Error> {
         let params = Params::Array(vec![to_json_value(keys)?, to_json_value(hash)?]);
-        let proof = self.client.request("state_getReadProof", params).await?;
+        let proof = self.client()?.request("state_getReadProof", params).await?;
         Ok(proof)
     }
 
@@ -338,25 +355,26 @@ impl<T: Runtime> Rpc<T> {
     ) -> Result<RuntimeVersion, Error> {
         let params = Params::Array(vec![to_json_value(at)?]);
         let version = self
-            .client
+            .client()?
             .request("state_getRuntimeVersion", params)
             .await?;
         Ok(version)
     }
 
+    /// Fetch the encoded System Events of a block.
+    pub async fn events(&self, hash: T::Hash) -> Result<Option<StorageData>, Error> {
+        self.storage(&events_storage_key(), Some(hash)).await
+    }
+
     /// Subscribe to substrate System Events
     pub async fn subscribe_events(
         &self,
     ) -> Result<Subscription<StorageChangeSet<T::Hash>>, Error> {
-        let mut storage_key = twox_128(b"System").to_vec();
-        storage_key.extend(twox_128(b"Events").to_vec());
-        log::debug!("Events storage key {:?}", hex::encode(&storage_key));
-
-        let keys = Some(vec![StorageKey(storage_key)]);
+        let keys = Some(vec![events_storage_key()]);
         let params = Params::Array(vec![to_json_value(keys)?]);
 
         let subscription = self
-            .client
+            .client()?
             .subscribe("state_subscribeStorage", params, "state_unsubscribeStorage")
             .await?;
         Ok(subscription)
@@ -365,7 +383,7 @@ impl<T: Runtime> Rpc<T> {
     /// Subscribe to blocks.
     pub async fn subscribe_blocks(&self) -> Result<Subscription<T::Header>, Error> {
         let subscription = self
-            .client
+            .client()?
             .subscribe(
                 "chain_subscribeNewHeads",
                 Params::None,
@@ -381,7 +399,7 @@ impl<T: Runtime> Rpc<T> {
         &self,
     ) -> Result<Subscription<T::Header>, Error> {
         let subscription = self
-            .client
+            .client()?
             .subscribe(
                 "chain_subscribeFinalizedHeads",
                 Params::None,
@@ -391,6 +409,21 @@ impl<T: Runtime> Rpc<T> {
         Ok(subscription)
     }
 
+    /// Subscribe to runtime version changes.
+    pub async fn subscribe_runtime_version(
+        &self,
+    ) -> Result<Subscription<RuntimeVersion>, Error> {
+        let subscription = self
+            .client()?
+            .subscribe(
+                "state_subscribeRuntimeVersion",
+                Params::None,
+                "state_unsubscribeRuntimeVersion",
+            )
+            .await?;
+        Ok(subscription)
+    }
+
     /// Create and submit an extrinsic and return corresponding Hash if successful
     pub async fn submit_extrinsic<E: Encode>(
         &self,
@@ -399,12 +432,23 @@ impl<T: Runtime> Rpc<T> {
         let bytes: Bytes = extrinsic.encode().into();
         let params = Params::Array(vec![to_json_value(bytes)?]);
         let xt_hash = self
-            .client
+            .client()?
             .request("author_submitExtrinsic", params)
             .await?;
         Ok(xt_hash)
     }
 
+    /// Dry run an extrinsic against the state of a block, the best block by default.
+    pub async fn dry_run(
+        &self,
+        encoded: Bytes,
+        at: Option<T::Hash>,
+    ) -> Result<ApplyExtrinsicResult, Error> {
+        let params = Params::Array(vec![to_json_value(encoded)?, to_json_value(at)?]);
+        let result: Bytes = self.client()?.request("system_dryRun", params).await?;
+        Ok(ApplyExtrinsicResult::decode(&mut &result.0[..])?)
+    }
+
     pub async fn watch_extrinsic<E: Encode>(
         &self,
         extrinsic: E,
@@ -412,7 +456,7 @@ impl<T: Runtime> Rpc<T> {
         let bytes: Bytes = extrinsic.encode().into();
         let params = Params::Array(vec![to_json_value(bytes)?]);
         let subscription = self
-            .client
+            .client()?
             .subscribe(
                 "author_submitAndWatchExtrinsic",
                 params,
@@ -422,84 +466,6 @@ impl<T: Runtime> Rpc<T> {
         Ok(subscription)
     }
 
-    /// Create and submit an extrinsic and return corresponding Event if successful
-    pub async fn submit_and_watch_extrinsic<E: Encode + 'static>(
-        &self,
-        extrinsic: E,
-        decoder: EventsDecoder<T>,
-    ) -> Result<ExtrinsicSuccess<T>, Error> {
-        let ext_hash = T::Hashing::hash_of(&extrinsic);
-        log::info!("Submitting Extrinsic `{:?}`", ext_hash);
-
-        let events_sub = self.subscribe_events().await?;
-        let mut xt_sub = self.watch_extrinsic(extrinsic).await?;
-
-        while let status = xt_sub.next().await {
-            // log::info!("received status {:?}", status);
-            match status {
-                // ignore in progress extrinsic for now
-                TransactionStatus::Future
-                | TransactionStatus::Ready
-                | TransactionStatus::Broadcast(_) => continue,
-                TransactionStatus::InBlock(block_hash) => {
-                    log::info!("Fetching block {:?}", block_hash);
-                    let block = self.block(Some(block_hash)).await?;
-                    return match block {
-                        Some(signed_block) => {
-                            log::info!(
-                                "Found block {:?}, with {} extrinsics",
-                                block_hash,
-                                signed_block.block.extrinsics.len()
-                            );
-                            let ext_index = signed_block
-                                .block
-                                .extrinsics
-                                .iter()
-                                .position(|ext| {
-                                    let hash = T::Hashing::hash_of(ext);
-                                    hash == ext_hash
-                                })
-                                .ok_or_else(|| {
-                                    Error::Other(format!(
-                                        "Failed to find Extrinsic with hash {:?}",
-                                        ext_hash,
-                                    ))
-                                })?;
-                            let mut sub = EventSubscription::new(events_sub, decoder);
-                            sub.filter_extrinsic(block_hash, ext_index);
-                            let mut events = vec![];
-                            while let Some(event) = sub.next().await {
-                                events.push(event?);
-                            }
-                            Ok(ExtrinsicSuccess {
-                                block: block_hash,
-                                extrinsic: ext_hash,
-                                events,
-                            })
-                        }
-                        None => {
-                            Err(format!("Failed to find block {:?}", block_hash).into())
-                        }
-                    }
-                }
-                TransactionStatus::Invalid => return Err("Extrinsic Invalid".into()),
-                TransactionStatus::Usurped(_) => return Err("Extrinsic Usurped".into()),
-                TransactionStatus::Dropped => return Err("Extrinsic Dropped".into()),
-                TransactionStatus::Retracted(_) => {
-                    return Err("Extrinsic Retracted".into())
-                }
-                // should have made it `InBlock` before either of these
-                TransactionStatus::Finalized(_) => {
-                    return Err("Extrinsic Finalized".into())
-                }
-                TransactionStatus::FinalityTimeout(_) => {
-                    return Err("Extrinsic FinalityTimeout".into())
-                }
-            }
-        }
-        unreachable!()
-    }
-
     /// Insert a key into the keystore.
     pub async fn insert_key(
         &self,
@@ -512,14 +478,14 @@ impl<T: Runtime> Rpc<T> {
             to_json_value(suri)?,
             to_json_value(public)?,
         ]);
-        self.client.request("author_insertKey", params).await?;
+        self.client()?.request("author_insertKey", params).await?;
         Ok(())
     }
 
     /// Generate new session keys and returns the corresponding public keys.
     pub async fn rotate_keys(&self) -> Result<Bytes, Error> {
         Ok(self
-            .client
+            .client()?
             .request("author_rotateKeys", Params::None)
             .await?)
     }
@@ -531,7 +497,10 @@ impl<T: Runtime> Rpc<T> {
     /// Returns `true` iff all private keys could be found.
     pub async fn has_session_keys(&self, session_keys: Bytes) -> Result<bool, Error> {
         let params = Params::Array(vec![to_json_value(session_keys)?]);
-        Ok(self.client.request("author_hasSessionKeys", params).await?)
+        Ok(self
+            .client()?
+            .request("author_hasSessionKeys", params)
+            .await?)
     }
 
     /// Checks if the keystore has private keys for the given public key and key type.
@@ -544,10 +513,18 @@ impl<T: Runtime> Rpc<T> {
     ) -> Result<bool, Error> {
         let params =
             Params::Array(vec![to_json_value(public_key)?, to_json_value(key_type)?]);
-        Ok(self.client.request("author_hasKey", params).await?)
+        Ok(self.client()?.request("author_hasKey", params).await?)
     }
 }
 
+/// Returns the storage key of the System Events.
+fn events_storage_key() -> StorageKey {
+    let mut storage_key = twox_128(b"System").to_vec();
+    storage_key.extend(twox_128(b"Events").to_vec());
+    log::debug!("Events storage key {:?}", hex::encode(&storage_key));
+    StorageKey(storage_key)
+}
+
 /// Captures data for when an extrinsic is successfully included in a block
 #[derive(Debug)]
 pub struct ExtrinsicSuccess<T: System> {
@@ -139,6 +139,7 @@ impl_opaque_keys! {
 }
 
 use crate::{
+    encoder::CallEncoder,
     extrinsic::{
         DefaultExtra,
         SignedExtra,
@@ -147,12 +148,28 @@ use crate::{
         balances::{
             AccountData,
             Balances,
+            BalancesCallEncoder,
+        },
+        contracts::{
+            Contracts,
+            ContractsCallEncoder,
+        },
+        session::{
+            Session,
+            SessionCallEncoder,
+        },
+        staking::{
+            Staking,
+            StakingCallEncoder,
+        },
+        sudo::{
+            Sudo,
+            SudoCallEncoder,
+        },
+        system::{
+            System,
+            SystemCallEncoder,
         },
-        contracts::Contracts,
-        session::Session,
-        staking::Staking,
-        sudo::Sudo,
-        system::System,
     },
 };
 
@@ -162,6 +179,12 @@ pub trait Runtime: System + Sized + Send + Sync + 'static {
     type Signature: Verify + Encode + Send + Sync + 'static;
     /// Transaction extras.
     type Extra: SignedExtra<Self> + Send + Sync + 'static;
+
+    /// Registers the types of the modules of the runtime with a call encoder, e.g. the
+    /// `Balance` of the balances module.
+    fn call_encoder(encoder: &mut CallEncoder<Self>) {
+        encoder.with_system();
+    }
 }
 
 /// Concrete type definitions compatible with those in the default substrate `node_runtime`
@@ -178,6 +201,14 @@ impl Staking for DefaultNodeRuntime {}
 impl Runtime for DefaultNodeRuntime {
     type Signature = MultiSignature;
     type Extra = DefaultExtra<Self>;
+
+    fn call_encoder(encoder: &mut CallEncoder<Self>) {
+        encoder.with_balances();
+        encoder.with_session();
+        encoder.with_staking();
+        encoder.with_contracts();
+        encoder.with_sudo();
+    }
 }
 
 impl System for DefaultNodeRuntime {
@@ -217,6 +248,12 @@ pub struct NodeTemplateRuntime;
 impl Runtime for NodeTemplateRuntime {
     type Signature = MultiSignature;
     type Extra = DefaultExtra<Self>;
+
+    fn call_encoder(encoder: &mut CallEncoder<Self>) {
+        encoder.with_balances();
+        encoder.with_session();
+        encoder.with_sudo();
+    }
 }
 
 impl System for NodeTemplateRuntime {
@@ -253,6 +290,12 @@ pub struct ContractsTemplateRuntime;
 impl Runtime for ContractsTemplateRuntime {
     type Signature = <NodeTemplateRuntime as Runtime>::Signature;
     type Extra = DefaultExtra<Self>;
+
+    fn call_encoder(encoder: &mut CallEncoder<Self>) {
+        encoder.with_balances();
+        encoder.with_contracts();
+        encoder.with_sudo();
+    }
 }
 
 impl System for ContractsTemplateRuntime {
@@ -287,6 +330,12 @@ pub struct KusamaRuntime;
 impl Runtime for KusamaRuntime {
     type Signature = MultiSignature;
     type Extra = DefaultExtra<Self>;
+
+    fn call_encoder(encoder: &mut CallEncoder<Self>) {
+        encoder.with_balances();
+        encoder.with_session();
+        encoder.with_staking();
+    }
 }
 
 impl System for KusamaRuntime {
@@ -21,26 +21,38 @@ use std::collections::VecDeque;
 use crate::{
     error::Error,
     events::{
+        EventRecord,
         EventsDecoder,
         Raw,
-        RawEvent,
     },
     frame::{
         system::Phase,
         Event,
     },
     runtimes::Runtime,
+    Client,
 };
 
 /// Event subscription simplifies filtering a storage change set stream for
 /// events of interest.
+///
+/// Events emitted in all phases of the block execution are returned, the phase is
+/// available on each `EventRecord`.
+///
+/// A subscription created with `Client::event_subscription` decodes the events of
+/// each block with the metadata of its runtime, otherwise the decoder is used for
+/// all blocks.
 pub struct EventSubscription<T: Runtime> {
     subscription: Subscription<StorageChangeSet<T::Hash>>,
     decoder: EventsDecoder<T>,
+    client: Option<Client<T>>,
+    runtime_decoder: Option<(u32, EventsDecoder<T>)>,
     block: Option<T::Hash>,
     extrinsic: Option<usize>,
+    phases: Option<Phases>,
     event: Option<(&'static str, &'static str)>,
-    events: VecDeque<RawEvent>,
+    topic: Option<T::Hash>,
+    events: VecDeque<Result<EventRecord<T>, Error>>,
     finished: bool,
 }
 
@@ -53,14 +65,28 @@ impl<T: Runtime> EventSubscription<T> {
         Self {
             subscription,
             decoder,
+            client: None,
+            runtime_decoder: None,
             block: None,
             extrinsic: None,
+            phases: None,
             event: None,
+            topic: None,
             events: Default::default(),
             finished: false,
         }
     }
 
+    pub(crate) fn for_client(
+        subscription: Subscription<StorageChangeSet<T::Hash>>,
+        decoder: EventsDecoder<T>,
+        client: Client<T>,
+    ) -> Self {
+        let mut events = Self::new(subscription, decoder);
+        events.client = Some(client);
+        events
+    }
+
     /// Only returns events contained in the block with the given hash.
     pub fn filter_block(&mut self, block: T::Hash) {
         self.block = Some(block);
@@ -72,16 +98,34 @@ impl<T: Runtime> EventSubscription<T> {
         self.extrinsic = Some(ext_index);
     }
 
+    /// Only returns events emitted by extrinsics.
+    pub fn filter_extrinsics(&mut self) {
+        self.phases = Some(Phases::Extrinsics);
+    }
+
+    /// Only returns events emitted by the block initialization and finalization hooks.
+    pub fn filter_block_hooks(&mut self) {
+        self.phases = Some(Phases::BlockHooks);
+    }
+
     /// Filters events by type.
     pub fn filter_event<E: Event<T>>(&mut self) {
         self.event = Some((E::MODULE, E::EVENT));
     }
 
+    /// Only returns events deposited with the given topic.
+    pub fn filter_topic(&mut self, topic: T::Hash) {
+        self.topic = Some(topic);
+    }
+
     /// Gets the next event.
-    pub async fn next(&mut self) -> Option<Result<RawEvent, Error>> {
+    ///
+    /// If a lenient decoder can not decode the rest of the events of a block, the
+    /// events decoded up to that point are followed by an `Error::Undecoded`.
+    pub async fn next(&mut self) -> Option<Result<EventRecord<T>, Error>> {
         loop {
             if let Some(event) = self.events.pop_front() {
-                return Some(Ok(event))
+                return Some(event)
             }
             if self.finished {
                 return None
@@ -94,33 +138,87 @@ impl<T: Runtime> EventSubscription<T> {
                     continue
                 }
             }
+            let decoder = match &self.client {
+                Some(client) => {
+                    match client
+                        .decoder_at(
+                            change_set.block,
+                            &self.decoder,
+                            &mut self.runtime_decoder,
+                        )
+                        .await
+                    {
+                        Ok(decoder) => decoder,
+                        Err(error) => return Some(Err(error)),
+                    }
+                }
+                None => &self.decoder,
+            };
             for (_key, data) in change_set.changes {
                 if let Some(data) = data {
-                    let raw_events = match self.decoder.decode_events(&mut &data.0[..]) {
+                    let raw_events = match decoder.decode_events(&mut &data.0[..]) {
                         Ok(events) => events,
                         Err(error) => return Some(Err(error)),
                     };
                     for (phase, raw) in raw_events {
-                        if let Phase::ApplyExtrinsic(i) = phase {
-                            if let Some(ext_index) = self.extrinsic {
-                                if i as usize != ext_index {
-                                    continue
+                        // the records after an undecoded one are skipped, which may
+                        // include events of interest whatever the filters
+                        if let Raw::Undecoded(undecoded) = raw {
+                            let error = Error::Undecoded(Box::new(undecoded));
+                            self.events.push_back(Err(error));
+                            continue
+                        }
+                        let is_extrinsic = match phase {
+                            Phase::ApplyExtrinsic(i) => {
+                                if let Some(ext_index) = self.extrinsic {
+                                    if i as usize != ext_index {
+                                        continue
+                                    }
                                 }
+                                true
                             }
-                            let event = match raw {
-                                Raw::Event(event) => event,
-                                Raw::Error(err) => return Some(Err(err.into())),
-                            };
-                            if let Some((module, variant)) = self.event {
-                                if event.module != module || event.variant != variant {
+                            Phase::Initialization | Phase::Finalization => {
+                                if self.extrinsic.is_some() {
                                     continue
                                 }
+                                false
                             }
-                            self.events.push_back(event);
+                        };
+                        match self.phases {
+                            Some(Phases::Extrinsics) if !is_extrinsic => continue,
+                            Some(Phases::BlockHooks) if is_extrinsic => continue,
+                            _ => {}
                         }
+                        let record = match raw {
+                            Raw::Event(record) => record,
+                            Raw::Error(err) => return Some(Err(err.into())),
+                            Raw::Undecoded(_) => {
+                                unreachable!("undecoded records are queued above; qed")
+                            }
+                        };
+                        if let Some((module, variant)) = self.event {
+                            if record.event.module != module
+                                || record.event.variant != variant
+                            {
+                                continue
+                            }
+                        }
+                        if let Some(topic) = self.topic.as_ref() {
+                            if !record.topics.contains(topic) {
+                                continue
+                            }
+                        }
+                        self.events.push_back(Ok(record));
                     }
                 }
             }
         }
     }
 }
+
+/// The phases of block execution to return events for.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Phases {
+    Extrinsics,
+    BlockHooks,
+}
@@ -0,0 +1,215 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of substrate-subxt.
+//
+// subxt is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// subxt is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-subxt.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Building, signing and submitting transactions.
+
+use codec::Encode;
+use sp_runtime::traits::Hash;
+
+use crate::{
+    error::{
+        Error,
+        RuntimeError,
+    },
+    events::EventsDecoder,
+    extrinsic::{
+        self,
+        ExtraContext,
+        Mortality,
+        SignOptions,
+        Signer,
+        UncheckedExtrinsic,
+    },
+    frame::{
+        system::{
+            AccountStoreExt,
+            System,
+        },
+        Call,
+    },
+    progress::TransactionProgress,
+    rpc::ExtrinsicSuccess,
+    runtimes::Runtime,
+    Client,
+};
+
+/// Builder of a signed transaction, returned by `Client::tx`.
+///
+/// Options which are not set are taken from the signer and the client, like
+/// `Client::create_signed` does.
+pub struct TransactionBuilder<'a, T: Runtime, C> {
+    client: &'a Client<T>,
+    call: C,
+    options: SignOptions<T>,
+}
+
+impl<'a, T: Runtime, C: Call<T> + Send + Sync> TransactionBuilder<'a, T, C> {
+    pub(crate) fn new(client: &'a Client<T>, call: C) -> Self {
+        Self {
+            client,
+            call,
+            options: SignOptions::default(),
+        }
+    }
+
+    /// Signs with a nonce instead of the nonce of the signer or the account.
+    pub fn nonce(mut self, nonce: T::Index) -> Self {
+        self.options.nonce = Some(nonce);
+        self
+    }
+
+    /// Sets the tip paid to the block author.
+    pub fn tip(mut self, tip: u128) -> Self {
+        self.options.tip = tip;
+        self
+    }
+
+    /// Makes the transaction valid for a period of blocks from the finalized head, or
+    /// from the checkpoint if one is set.
+    pub fn mortal(mut self, period: u64) -> Self {
+        self.options.mortality = Some(Mortality::Mortal(period));
+        self
+    }
+
+    /// Makes a mortal transaction valid from the block with the given number and hash,
+    /// instead of from the finalized head. Offline clients have to set a checkpoint to
+    /// sign mortal transactions.
+    pub fn checkpoint(mut self, number: T::BlockNumber, hash: T::Hash) -> Self {
+        self.options.checkpoint = Some((number, hash));
+        self
+    }
+
+    /// Makes the transaction valid forever.
+    pub fn immortal(mut self) -> Self {
+        self.options.mortality = Some(Mortality::Immortal);
+        self
+    }
+
+    /// Replaces all the options.
+    pub fn options(mut self, options: SignOptions<T>) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Signs the transaction.
+    pub async fn sign(
+        self,
+        signer: &(dyn Signer<T> + Send + Sync),
+    ) -> Result<SignedTransaction<T>, Error> {
+        let Self {
+            client,
+            call,
+            options,
+        } = self;
+        let nonce = if let Some(nonce) = options.nonce.or_else(|| signer.nonce()) {
+            nonce
+        } else {
+            client.account(signer.account_id(), None).await?.nonce
+        };
+        let decoder = client.events_decoder::<C>();
+        // the call is encoded for the runtime version it is signed for
+        let (runtime_version, metadata) = client.runtime.current();
+        let call = metadata
+            .module_with_calls(C::MODULE)
+            .and_then(|module| module.call(C::FUNCTION, call))?;
+        let mut context = ExtraContext::new(&runtime_version, client.genesis_hash, nonce);
+        context.tip = options.tip;
+        if let Mortality::Mortal(period) = options.mortality.unwrap_or(client.mortality) {
+            let (era, era_hash) = client.mortal_era(period, options.checkpoint).await?;
+            context.set_era(era, era_hash);
+        }
+        let extrinsic = extrinsic::create_signed(context.clone(), call, signer).await?;
+        Ok(SignedTransaction {
+            client: client.clone(),
+            extrinsic,
+            context,
+            decoder,
+        })
+    }
+}
+
+/// A signed transaction which is ready to be submitted.
+pub struct SignedTransaction<T: Runtime> {
+    client: Client<T>,
+    extrinsic: UncheckedExtrinsic<T>,
+    context: ExtraContext<T>,
+    decoder: EventsDecoder<T>,
+}
+
+impl<T: Runtime> SignedTransaction<T> {
+    /// Returns the hash of the transaction, which the node returns when it is
+    /// submitted.
+    pub fn hash(&self) -> T::Hash {
+        <T as System>::Hashing::hash_of(&self.extrinsic)
+    }
+
+    /// Returns the SCALE encoded transaction.
+    pub fn encoded(&self) -> Vec<u8> {
+        self.extrinsic.encode()
+    }
+
+    /// Returns the context the transaction was signed with, e.g. its nonce, era and
+    /// tip.
+    pub fn context(&self) -> &ExtraContext<T> {
+        &self.context
+    }
+
+    /// Returns the signed extrinsic.
+    pub fn extrinsic(&self) -> &UncheckedExtrinsic<T> {
+        &self.extrinsic
+    }
+
+    /// Returns the signed extrinsic, dropping the rest.
+    pub fn into_extrinsic(self) -> UncheckedExtrinsic<T> {
+        self.extrinsic
+    }
+
+    /// Applies the transaction to the state of the best block without submitting it.
+    ///
+    /// Fails with `Error::Invalid` if the transaction would not be included, or with
+    /// `Error::Runtime` if its call would fail.
+    pub async fn dry_run(&self) -> Result<(), Error> {
+        match self.client.rpc.dry_run(self.encoded().into(), None).await? {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(error)) => {
+                let metadata = self.client.metadata();
+                Err(RuntimeError::from_dispatch(&metadata, error)?.into())
+            }
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// Submits the transaction and returns its hash.
+    pub async fn submit(self) -> Result<T::Hash, Error> {
+        self.client.submit_extrinsic(self.extrinsic).await
+    }
+
+    /// Submits the transaction and waits for it to be included in a block, returning
+    /// its events.
+    pub async fn watch(self) -> Result<ExtrinsicSuccess<T>, Error> {
+        self.client
+            .submit_and_watch_extrinsic(self.extrinsic, self.decoder)
+            .await
+    }
+
+    /// Submits the transaction and returns its progress, e.g. for waiting until it is
+    /// finalized.
+    pub async fn progress(self) -> Result<TransactionProgress<T>, Error> {
+        self.client
+            .submit_and_watch_progress(self.extrinsic, self.decoder)
+            .await
+    }
+}
@@ -0,0 +1,310 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of substrate-subxt.
+//
+// subxt is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// subxt is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-subxt.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Following runtime upgrades of the chain.
+
+use futures::{
+    channel::{
+        mpsc,
+        oneshot,
+    },
+    future,
+    pin_mut,
+    stream::Stream,
+    task::{
+        Context,
+        Poll,
+    },
+};
+use sp_version::RuntimeVersion;
+use std::{
+    collections::HashMap,
+    pin::Pin,
+    sync::{
+        Arc,
+        Mutex,
+        RwLock,
+        Weak,
+    },
+};
+
+use crate::{
+    metadata::Metadata,
+    rpc::Rpc,
+    runtimes::Runtime,
+};
+
+/// A runtime upgrade of the chain.
+#[derive(Clone, Debug)]
+pub struct RuntimeUpgrade {
+    /// Runtime version after the upgrade.
+    pub runtime_version: RuntimeVersion,
+    /// Metadata of the upgraded runtime.
+    pub metadata: Arc<Metadata>,
+}
+
+/// Stream of the runtime upgrades of the chain.
+///
+/// Ends when the client stops following runtime upgrades, or right away if it does not
+/// follow them.
+pub struct RuntimeUpgrades {
+    receiver: mpsc::UnboundedReceiver<RuntimeUpgrade>,
+}
+
+impl Stream for RuntimeUpgrades {
+    type Item = RuntimeUpgrade;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.receiver).poll_next(cx)
+    }
+}
+
+/// The runtime version and metadata of the chain, shared by the clones of a client.
+///
+/// The metadata of every runtime version seen is cached by spec version, for decoding
+/// blocks of earlier runtimes.
+pub(crate) struct RuntimeState {
+    current: RwLock<(RuntimeVersion, Arc<Metadata>)>,
+    versions: RwLock<HashMap<u32, Arc<Metadata>>>,
+    listeners: Mutex<Listeners>,
+}
+
+/// The streams of runtime upgrades, which are only notified while the runtime upgrades
+/// are followed.
+#[derive(Default)]
+struct Listeners {
+    following: bool,
+    senders: Vec<mpsc::UnboundedSender<RuntimeUpgrade>>,
+    /// Dropped with the state, to stop following the runtime upgrades.
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl RuntimeState {
+    pub fn new(runtime_version: RuntimeVersion, metadata: Metadata) -> Self {
+        let metadata = Arc::new(metadata);
+        let mut versions = HashMap::new();
+        versions.insert(runtime_version.spec_version, metadata.clone());
+        Self {
+            current: RwLock::new((runtime_version, metadata)),
+            versions: RwLock::new(versions),
+            listeners: Default::default(),
+        }
+    }
+
+    /// Returns the current runtime version and the metadata belonging to it.
+    pub fn current(&self) -> (RuntimeVersion, Arc<Metadata>) {
+        self.current.read().expect("lock poisoned").clone()
+    }
+
+    /// Returns the current runtime version.
+    pub fn runtime_version(&self) -> RuntimeVersion {
+        self.current().0
+    }
+
+    /// Returns the current metadata.
+    pub fn metadata(&self) -> Arc<Metadata> {
+        self.current().1
+    }
+
+    /// Returns the cached metadata of a spec version.
+    pub fn metadata_of(&self, spec_version: u32) -> Option<Arc<Metadata>> {
+        self.versions
+            .read()
+            .expect("lock poisoned")
+            .get(&spec_version)
+            .cloned()
+    }
+
+    /// Caches the metadata of a spec version, returning the metadata cached for it.
+    ///
+    /// Metadata which was cached before is kept, so that all users of a spec version
+    /// share the same metadata.
+    pub fn cache(&self, spec_version: u32, metadata: Metadata) -> Arc<Metadata> {
+        self.versions
+            .write()
+            .expect("lock poisoned")
+            .entry(spec_version)
+            .or_insert_with(|| Arc::new(metadata))
+            .clone()
+    }
+
+    /// Replaces the runtime version and metadata and notifies the listeners.
+    pub fn upgrade(&self, runtime_version: RuntimeVersion, metadata: Metadata) {
+        let metadata = self.cache(runtime_version.spec_version, metadata);
+        *self.current.write().expect("lock poisoned") =
+            (runtime_version.clone(), metadata.clone());
+        let upgrade = RuntimeUpgrade {
+            runtime_version,
+            metadata,
+        };
+        self.listeners
+            .lock()
+            .expect("lock poisoned")
+            .senders
+            .retain(|listener| listener.unbounded_send(upgrade.clone()).is_ok());
+    }
+
+    /// Returns a stream of the following runtime upgrades, which ends right away if
+    /// the runtime upgrades are not followed.
+    pub fn subscribe(&self) -> RuntimeUpgrades {
+        let (sender, receiver) = mpsc::unbounded();
+        let mut listeners = self.listeners.lock().expect("lock poisoned");
+        if listeners.following {
+            listeners.senders.push(sender);
+        }
+        RuntimeUpgrades { receiver }
+    }
+
+    /// Marks the runtime upgrades as followed until the returned guard is dropped.
+    ///
+    /// Returns `None` if they are followed already.
+    pub fn start_following(state: &Arc<Self>) -> Option<Following> {
+        let mut listeners = state.listeners.lock().expect("lock poisoned");
+        if listeners.following {
+            return None
+        }
+        let (sender, shutdown) = oneshot::channel();
+        listeners.following = true;
+        listeners.shutdown = Some(sender);
+        Some(Following {
+            state: Arc::downgrade(state),
+            shutdown,
+        })
+    }
+
+    /// Ends the streams of runtime upgrades.
+    fn close(&self) {
+        let mut listeners = self.listeners.lock().expect("lock poisoned");
+        listeners.following = false;
+        listeners.senders.clear();
+        listeners.shutdown = None;
+    }
+}
+
+/// Guard of following the runtime upgrades, which ends the streams of runtime
+/// upgrades when dropped.
+pub(crate) struct Following {
+    state: Weak<RuntimeState>,
+    /// Resolves when the state is dropped.
+    shutdown: oneshot::Receiver<()>,
+}
+
+impl Drop for Following {
+    fn drop(&mut self) {
+        if let Some(state) = self.state.upgrade() {
+            state.close();
+        }
+    }
+}
+
+/// Follows the runtime upgrades until the state is dropped or the subscription to the
+/// runtime version fails.
+pub(crate) async fn follow_runtime_upgrades<T: Runtime>(
+    rpc: Rpc<T>,
+    mut following: Following,
+) {
+    let follow = follow(rpc, following.state.clone());
+    pin_mut!(follow);
+    future::select(follow, &mut following.shutdown).await;
+}
+
+/// Fetches the metadata whenever the node reports a new runtime version.
+async fn follow<T: Runtime>(rpc: Rpc<T>, state: Weak<RuntimeState>) {
+    let mut subscription = match rpc.subscribe_runtime_version().await {
+        Ok(subscription) => subscription,
+        Err(err) => {
+            log::warn!("Not following runtime upgrades: {}", err);
+            return
+        }
+    };
+    loop {
+        let runtime_version = subscription.next().await;
+        let runtime = if let Some(runtime) = state.upgrade() {
+            runtime
+        } else {
+            return
+        };
+        let current = runtime.runtime_version();
+        if runtime_version.spec_version == current.spec_version
+            && runtime_version.transaction_version == current.transaction_version
+        {
+            continue
+        }
+        log::info!(
+            "Runtime upgraded from version {} to {}",
+            current.spec_version,
+            runtime_version.spec_version
+        );
+        match rpc.metadata(None).await {
+            Ok(metadata) => runtime.upgrade(runtime_version, metadata),
+            Err(err) => {
+                log::error!(
+                    "Failed to fetch metadata of runtime version {}: {}",
+                    runtime_version.spec_version,
+                    err
+                )
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+
+    #[async_std::test]
+    async fn test_runtime_upgrade_notifies_listeners() {
+        let state = Arc::new(RuntimeState::new(Default::default(), Default::default()));
+        let following = RuntimeState::start_following(&state).unwrap();
+        assert!(RuntimeState::start_following(&state).is_none());
+        let mut upgrades = state.subscribe();
+        let runtime_version = RuntimeVersion {
+            spec_version: 2,
+            ..Default::default()
+        };
+        state.upgrade(runtime_version.clone(), Default::default());
+
+        assert_eq!(state.runtime_version(), runtime_version);
+        let upgrade = upgrades.next().await.unwrap();
+        assert_eq!(upgrade.runtime_version, runtime_version);
+        assert!(Arc::ptr_eq(&upgrade.metadata, &state.metadata()));
+
+        drop(following);
+        assert!(upgrades.next().await.is_none());
+        assert!(state.subscribe().next().await.is_none());
+    }
+
+    #[async_std::test]
+    async fn test_runtime_upgrades_end_when_not_followed() {
+        let state = RuntimeState::new(Default::default(), Default::default());
+        let mut upgrades = state.subscribe();
+        state.upgrade(Default::default(), Default::default());
+        assert!(upgrades.next().await.is_none());
+    }
+
+    #[test]
+    fn test_metadata_cached_by_spec_version() {
+        let state = RuntimeState::new(Default::default(), Default::default());
+        assert!(state.metadata_of(0).is_some());
+        assert!(state.metadata_of(1).is_none());
+
+        let metadata = state.cache(1, Default::default());
+        assert!(Arc::ptr_eq(&state.metadata_of(1).unwrap(), &metadata));
+        let cached = state.cache(1, Default::default());
+        assert!(Arc::ptr_eq(&cached, &metadata));
+    }
+}
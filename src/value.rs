@@ -0,0 +1,138 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of substrate-subxt.
+//
+// subxt is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// subxt is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-subxt.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Dynamically decoded values.
+
+use serde::{
+    ser::{
+        SerializeMap,
+        SerializeSeq,
+    },
+    Serialize,
+    Serializer,
+};
+
+/// A value decoded from its SCALE encoding using only the type names in the metadata.
+///
+/// Byte-like values serialize as `0x` prefixed hex strings, composites and sequences as
+/// arrays and variants as single entry maps.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Value {
+    /// A boolean.
+    Bool(bool),
+    /// An unsigned integer.
+    UInt(u128),
+    /// A signed integer.
+    Int(i128),
+    /// A byte string, or the raw bytes of a type without a known representation.
+    Bytes(Vec<u8>),
+    /// An account id.
+    AccountId(Vec<u8>),
+    /// A hash.
+    Hash(Vec<u8>),
+    /// An optional value.
+    Option(Option<Box<Value>>),
+    /// A sequence of values of the same type, e.g. a `Vec` or an array.
+    Sequence(Vec<Value>),
+    /// A fixed number of values of different types, e.g. a tuple or the arguments of an event.
+    Composite(Vec<Value>),
+    /// A named enum variant with its value, e.g. `Ok` or `Err` of a `Result`.
+    Variant(String, Box<Value>),
+}
+
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Value::Bool(value) => serializer.serialize_bool(*value),
+            Value::UInt(value) => serializer.serialize_u128(*value),
+            Value::Int(value) => serializer.serialize_i128(*value),
+            Value::Bytes(bytes) | Value::AccountId(bytes) | Value::Hash(bytes) => {
+                serializer.serialize_str(&format!("0x{}", hex::encode(bytes)))
+            }
+            Value::Option(None) => serializer.serialize_none(),
+            Value::Option(Some(value)) => serializer.serialize_some(value),
+            Value::Sequence(values) | Value::Composite(values) => {
+                let mut seq = serializer.serialize_seq(Some(values.len()))?;
+                for value in values {
+                    seq.serialize_element(value)?;
+                }
+                seq.end()
+            }
+            Value::Variant(name, value) => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry(name, value)?;
+                map.end()
+            }
+        }
+    }
+}
+
+/// How the bytes of a registered type are represented as a `Value`.
+pub(crate) enum PrimitiveKind {
+    UInt,
+    AccountId,
+    Hash,
+}
+
+impl PrimitiveKind {
+    /// Returns the kind of the primitive type with the given name, if it is known.
+    pub(crate) fn of(name: &str) -> Option<Self> {
+        match name {
+            "AccountId" | "ValidatorId" => Some(Self::AccountId),
+            "Hash" | "CodeHash" => Some(Self::Hash),
+            "Balance" | "BlockNumber" | "Index" | "AccountIndex" | "SessionIndex"
+            | "EraIndex" | "ReferendumIndex" | "PropIndex" | "ProposalIndex"
+            | "AuthorityIndex" | "AuthorityWeight" | "MemberCount" | "Gas" => {
+                Some(Self::UInt)
+            }
+            _ => None,
+        }
+    }
+
+    /// Converts the encoded bytes of a value of this kind into a `Value`.
+    pub(crate) fn to_value(&self, bytes: &[u8]) -> Value {
+        match self {
+            Self::UInt if bytes.len() <= 16 => {
+                let mut buf = [0u8; 16];
+                buf[..bytes.len()].copy_from_slice(bytes);
+                Value::UInt(u128::from_le_bytes(buf))
+            }
+            Self::UInt => Value::Bytes(bytes.to_vec()),
+            Self::AccountId => Value::AccountId(bytes.to_vec()),
+            Self::Hash => Value::Hash(bytes.to_vec()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialize_value() {
+        let value = Value::Composite(vec![
+            Value::AccountId(vec![1, 2]),
+            Value::UInt(u128::max_value()),
+            Value::Option(None),
+            Value::Sequence(vec![Value::Bool(true), Value::Int(-1)]),
+            Value::Variant("Err".into(), Box::new(Value::Bytes(vec![]))),
+        ]);
+        assert_eq!(
+            serde_json::to_string(&value).unwrap(),
+            r#"["0x0102",340282366920938463463374607431768211455,null,[true,-1],{"Err":"0x"}]"#
+        );
+    }
+}